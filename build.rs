@@ -15,18 +15,18 @@ fn main() {
     let mut cmd = cli();
 
     // Generate completion scripts for all supported shells
-    generate_to(Bash, &mut cmd, "autocomplete-app", &dest_path).unwrap();
-    println!("Generated completion script for {} shell", "Bash");
+    generate_to(Bash, &mut cmd, "autocomplete-app", dest_path).unwrap();
+    println!("Generated completion script for Bash shell");
 
-    generate_to(Zsh, &mut cmd, "autocomplete-app", &dest_path).unwrap();
-    println!("Generated completion script for {} shell", "Zsh");
+    generate_to(Zsh, &mut cmd, "autocomplete-app", dest_path).unwrap();
+    println!("Generated completion script for Zsh shell");
 
-    generate_to(Fish, &mut cmd, "autocomplete-app", &dest_path).unwrap();
-    println!("Generated completion script for {} shell", "Fish");
+    generate_to(Fish, &mut cmd, "autocomplete-app", dest_path).unwrap();
+    println!("Generated completion script for Fish shell");
 
-    generate_to(Elvish, &mut cmd, "autocomplete-app", &dest_path).unwrap();
-    println!("Generated completion script for {} shell", "Elvish");
+    generate_to(Elvish, &mut cmd, "autocomplete-app", dest_path).unwrap();
+    println!("Generated completion script for Elvish shell");
 
-    generate_to(PowerShell, &mut cmd, "autocomplete-app", &dest_path).unwrap();
-    println!("Generated completion script for {} shell", "PowerShell");
+    generate_to(PowerShell, &mut cmd, "autocomplete-app", dest_path).unwrap();
+    println!("Generated completion script for PowerShell shell");
 }
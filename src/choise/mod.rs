@@ -5,14 +5,15 @@ pub fn print_choice(text: &str, choices: Vec<&str>) -> Result<String, std::io::E
         choices
             .iter()
             .filter(|c| !c.is_empty())
-            .map(|c| *c)
+            .copied()
             .collect::<Vec<&str>>()
             .join("/")
     );
 
     let mut input = String::new();
     loop {
-        std::io::stdin().read_line(&mut input).unwrap();
+        input.clear();
+        std::io::stdin().read_line(&mut input)?;
         input = input.trim().to_lowercase();
 
         if choices.iter().any(|c| c.to_lowercase() == input) {
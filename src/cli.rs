@@ -1,13 +1,77 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
+use std::ffi::OsString;
 
 pub fn cli() -> Command {
     Command::new("searchhelp")
         .about("CLI for managing and searching help texts of applications")
+        .after_help(
+            "Exit codes: 0 success, 1 no matches, 2 invalid input or unknown program, \
+             3 database error, 4 I/O error, 5 help command failed",
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Show the causes of errors"),
+        )
+        .arg(
+            Arg::new("db")
+                .long("db")
+                .global(true)
+                .value_name("PATH")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .help("Database file to use, instead of $SEARCHHELP_DB or the one in the data directory"),
+        )
         .subcommand(
             Command::new("add")
                 .about("Add a new program")
                 .arg(Arg::new("name").required(true))
-                .arg(Arg::new("command").required(true)),
+                .arg(Arg::new("command").required_unless_present("man").help(
+                    "Help commands separated by ';', end a command with 2>&1 to include its stderr",
+                ))
+                .arg(
+                    Arg::new("man")
+                        .long("man")
+                        .action(ArgAction::SetTrue)
+                        .help("Also use the man page of the program, read from MANPATH"),
+                )
+                .arg(
+                    Arg::new("shell")
+                        .long("shell")
+                        .action(ArgAction::SetTrue)
+                        .help(
+                        "Run each command with `sh -c`, for pipelines like 'tool --help | col -b'",
+                    ),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .value_parser(clap::value_parser!(u64))
+                        .help("Seconds each command may run before it is killed [default: 10]"),
+                )
+                .arg(
+                    Arg::new("max-output")
+                        .long("max-output")
+                        .value_parser(clap::value_parser!(usize))
+                        .help(
+                            "Bytes each command may print before it is killed [default: 4194304]",
+                        ),
+                )
+                .arg(
+                    Arg::new("keep-raw")
+                        .long("keep-raw")
+                        .action(ArgAction::SetTrue)
+                        .help("Also store the raw output of the commands, for debugging"),
+                )
+                .arg(
+                    Arg::new("depth")
+                        .long("depth")
+                        .short('d')
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("0")
+                        .help("Also store the help texts of subcommands this many levels deep"),
+                ),
         )
         .subcommand(
             Command::new("update")
@@ -19,9 +83,336 @@ pub fn cli() -> Command {
                 .about("Delete a program by name or by selecting it from the list of programs")
                 .arg(Arg::new("name").required(false)),
         )
+        .subcommand(
+            Command::new("refresh")
+                .about("Run the stored help commands again and update the help texts that changed")
+                .arg(
+                    Arg::new("names")
+                        .num_args(1..)
+                        .required_unless_present("all")
+                        .help("Programs to refresh, along with their subcommands"),
+                )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("names")
+                        .help("Refresh every program"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Only report what changed, without updating anything"),
+                )
+                .arg(
+                    Arg::new("skip-unchanged")
+                        .long("skip-unchanged")
+                        .action(ArgAction::SetTrue)
+                        .help("Skip programs whose binary has not changed, unless they read man pages"),
+                ),
+        )
+        .subcommand(
+            Command::new("alias")
+                .about("Manage other names programs can be referred to by")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("add")
+                        .about("Add aliases for a program")
+                        .arg(Arg::new("name").required(true))
+                        .arg(Arg::new("aliases").required(true).num_args(1..)),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Remove aliases")
+                        .arg(Arg::new("aliases").required(true).num_args(1..)),
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("List aliases, of all programs or of one")
+                        .arg(Arg::new("name")),
+                ),
+        )
+        .subcommand(
+            Command::new("tag")
+                .about("Manage the tags programs are grouped by")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("add")
+                        .about("Add tags to a program")
+                        .arg(Arg::new("name").required(true))
+                        .arg(Arg::new("tags").required(true).num_args(1..)),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Remove tags from a program")
+                        .arg(Arg::new("name").required(true))
+                        .arg(Arg::new("tags").required(true).num_args(1..)),
+                ),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List the stored programs")
+                .arg(
+                    Arg::new("sort")
+                        .long("sort")
+                        .value_parser(["name", "updated", "size"])
+                        .default_value("name")
+                        .help("Sort by name, by most recently updated or by largest help text"),
+                )
+                .arg(
+                    Arg::new("filter")
+                        .long("filter")
+                        .value_name("GLOB")
+                        .help("Only list programs whose name matches, e.g. 'docker*'"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["table", "json", "csv", "names"])
+                        .default_value("table")
+                        .help("Print a table, JSON, CSV or only the names, one per line"),
+                ),
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Read the stored help text of a program")
+                .arg(Arg::new("name").required(true)),
+        )
+        .subcommand(
+            Command::new("info")
+                .about("Show where a program's help text came from")
+                .arg(Arg::new("name").required(true)),
+        )
+        .subcommand(
+            Command::new("history")
+                .about("List the help texts stored for a program over time")
+                .arg(Arg::new("name").required(true)),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Show how the help text of a program changed between two revisions")
+                .arg(Arg::new("name").required(true))
+                .arg(
+                    Arg::new("rev-a")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Older revision, the one before the newest by default"),
+                )
+                .arg(
+                    Arg::new("rev-b")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Newer revision, the newest by default"),
+                ),
+        )
         .subcommand(
             Command::new("search")
                 .about("Search help texts")
-                .arg(Arg::new("query").required(true)),
+                .after_help(
+                    "Put -- before a query that is an option of search itself: \
+                     searchhelp search -- --regex -p ls",
+                )
+                .arg(Arg::new("query").required(true).allow_hyphen_values(true).help(
+                    "Words to search for, programs and lines matching any of them are shown \
+                     and programs matching more of them rank higher",
+                ))
+                .arg(
+                    Arg::new("fuzzy")
+                        .long("fuzzy")
+                        .action(ArgAction::SetTrue)
+                        .help("Also match lines that are close to the query, e.g. with a typo"),
+                )
+                .arg(
+                    Arg::new("options")
+                        .long("options")
+                        .short('o')
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("fuzzy")
+                        .help("Search the parsed options and print one line per option"),
+                )
+                .arg(
+                    Arg::new("regex")
+                        .long("regex")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("fuzzy")
+                        .help("Treat the query as a regular expression"),
+                )
+                .arg(
+                    Arg::new("ignore-case")
+                        .long("ignore-case")
+                        .short('i')
+                        .action(ArgAction::SetTrue)
+                        .overrides_with("case-sensitive")
+                        .help("Ignore case when matching (default)"),
+                )
+                .arg(
+                    Arg::new("case-sensitive")
+                        .long("case-sensitive")
+                        .short('s')
+                        .action(ArgAction::SetTrue)
+                        .overrides_with("ignore-case")
+                        .help("Match case exactly"),
+                )
+                .arg(
+                    Arg::new("word")
+                        .long("word")
+                        .short('w')
+                        .action(ArgAction::SetTrue)
+                        .help("Only match whole words"),
+                )
+                .arg(
+                    Arg::new("program")
+                        .long("program")
+                        .short('p')
+                        .action(ArgAction::Append)
+                        .help("Only search these programs, globs like 'docker*' are allowed"),
+                )
+                .arg(
+                    Arg::new("section")
+                        .long("section")
+                        .action(ArgAction::Append)
+                        .value_parser([
+                            "name",
+                            "synopsis",
+                            "description",
+                            "options",
+                            "commands",
+                            "examples",
+                            "other",
+                        ])
+                        .help("Only search sections of this kind, e.g. options or examples"),
+                )
+                .arg(
+                    Arg::new("after-context")
+                        .long("after-context")
+                        .short('A')
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Show this many lines after each match"),
+                )
+                .arg(
+                    Arg::new("before-context")
+                        .long("before-context")
+                        .short('B')
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Show this many lines before each match"),
+                )
+                .arg(
+                    Arg::new("context")
+                        .long("context")
+                        .short('C')
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Show this many lines around each match instead of its option block"),
+                )
+                .arg(
+                    Arg::new("color")
+                        .long("color")
+                        .value_parser(["auto", "always", "never"])
+                        .default_value("auto")
+                        .help("When to color the results, auto honours NO_COLOR and pipes"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["text", "json", "jsonl"])
+                        .default_value("text")
+                        .conflicts_with("options")
+                        .help("Print the matched lines as text, a JSON array or one JSON object per line"),
+                ),
         )
 }
+
+// The search query accepts values starting with a dash, so clap would read attached context
+// values like -A1 as the query, they are split into -A 1 before parsing. The query is the only
+// positional argument, so -- only escapes the word after it and options can still follow
+pub fn normalize_search_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut in_search = false;
+    let mut normalized = Vec::new();
+    let mut escaped = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            if !in_search || escaped.is_some() {
+                normalized.push(arg);
+                normalized.extend(args.by_ref());
+                break;
+            }
+            escaped = Some(args.next());
+            continue;
+        }
+
+        let attached = arg
+            .to_str()
+            .filter(|_| in_search)
+            .and_then(|a| a.strip_prefix('-'))
+            .filter(|a| a.len() > 1 && a.starts_with(['A', 'B', 'C']))
+            .filter(|a| a[1..].bytes().all(|b| b.is_ascii_digit()))
+            .map(|a| a.split_at(1));
+        match attached {
+            Some((flag, value)) => {
+                normalized.push(format!("-{}", flag).into());
+                normalized.push(value.into());
+            }
+            None => {
+                in_search |= arg == "search";
+                normalized.push(arg);
+            }
+        }
+    }
+
+    if let Some(query) = escaped {
+        normalized.push("--".into());
+        normalized.extend(query);
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(args: &[&str]) -> clap::error::Result<clap::ArgMatches> {
+        let args = ["searchhelp", "search"]
+            .iter()
+            .chain(args)
+            .map(OsString::from);
+        let matches = cli().try_get_matches_from(normalize_search_args(args))?;
+        Ok(matches.subcommand_matches("search").unwrap().clone())
+    }
+
+    fn query(matches: &clap::ArgMatches) -> &str {
+        matches.get_one::<String>("query").unwrap()
+    }
+
+    #[test]
+    fn accepts_queries_starting_with_a_dash() {
+        let matches = search(&["--force"]).unwrap();
+        assert_eq!(query(&matches), "--force");
+
+        let matches = search(&["--all", "-p", "ls"]).unwrap();
+        assert_eq!(query(&matches), "--all");
+        assert_eq!(matches.get_one::<String>("program").unwrap(), "ls");
+
+        let matches = search(&["-p", "ls", "--", "--regex"]).unwrap();
+        assert_eq!(query(&matches), "--regex");
+        assert!(!matches.get_flag("regex"));
+
+        let matches = search(&["--", "--all", "-p", "ls", "-C2"]).unwrap();
+        assert_eq!(query(&matches), "--all");
+        assert_eq!(matches.get_one::<String>("program").unwrap(), "ls");
+        assert_eq!(matches.get_one::<usize>("context"), Some(&2));
+
+        assert!(search(&["--", "one", "two"]).is_err());
+    }
+
+    #[test]
+    fn reads_attached_context_values() {
+        let matches = search(&["-A1", "-B", "2", "-C3", "foo"]).unwrap();
+        assert_eq!(query(&matches), "foo");
+        assert_eq!(matches.get_one::<usize>("after-context"), Some(&1));
+        assert_eq!(matches.get_one::<usize>("before-context"), Some(&2));
+        assert_eq!(matches.get_one::<usize>("context"), Some(&3));
+
+        let matches = search(&["--", "-A1"]).unwrap();
+        assert_eq!(query(&matches), "-A1");
+    }
+}
@@ -0,0 +1,198 @@
+use crate::command::{get_command_text, help_command::HelpCommand, shell_words::split_words};
+use crate::db::program_binary::ProgramBinary;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+// Arguments tried in order to get a program to print its version
+const VERSION_PROBES: [&str; 3] = ["--version", "-V", "version"];
+// Version probes get less time and room than help commands
+const VERSION_TIMEOUT_SECS: u64 = 3;
+const VERSION_MAX_OUTPUT: usize = 64 * 1024;
+// Only the start of the version output is kept, the rest is usually license text
+const VERSION_MAX_LINES: usize = 5;
+
+// Get the name of the executable behind a program's help commands, shell commands use their
+// first word and man pages the first word of the program name
+pub fn binary_name(name: &str, commands: &[HelpCommand]) -> Option<String> {
+    let executable = commands.iter().find(|c| !c.man).and_then(|c| {
+        if c.is_shell() {
+            split_words(&c.argv[2]).ok()?.into_iter().next()
+        } else {
+            c.argv.first().cloned()
+        }
+    });
+
+    executable.or_else(|| name.split_whitespace().next().map(str::to_string))
+}
+
+// Resolve an executable name to an absolute path the way the shell does, through PATH
+pub fn resolve_binary(name: &str) -> Option<PathBuf> {
+    if name.contains('/') {
+        return fs::canonicalize(name).ok().filter(|p| is_executable(p));
+    }
+
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| is_executable(path))
+        .and_then(|path| fs::canonicalize(path).ok())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+// Other systems have no execute bit, any file found is taken
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file())
+}
+
+// Look at a binary without running it, the version is probed separately
+pub fn inspect_binary(path: &Path) -> io::Result<ProgramBinary> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+
+    Ok(ProgramBinary {
+        path: path.to_string_lossy().to_string(),
+        modified,
+        size: metadata.len(),
+        hash: hash_file(path)?,
+        version_output: None,
+        checked_at: None,
+    })
+}
+
+// Check whether a binary is still the file that was stored, files that were only touched
+// are compared by their contents
+pub fn binary_unchanged(stored: &ProgramBinary, path: &Path) -> bool {
+    if stored.path != path.to_string_lossy() {
+        return false;
+    }
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs() as i64);
+    if modified == stored.modified && metadata.len() == stored.size {
+        return true;
+    }
+
+    metadata.len() == stored.size && hash_file(path).is_ok_and(|hash| hash == stored.hash)
+}
+
+// Run the version probes until one succeeds and prints something
+pub fn probe_version(path: &Path) -> Option<String> {
+    VERSION_PROBES.iter().find_map(|probe| {
+        let command = HelpCommand {
+            argv: vec![path.to_string_lossy().to_string(), probe.to_string()],
+            include_stderr: false,
+            timeout_secs: Some(VERSION_TIMEOUT_SECS),
+            max_output: Some(VERSION_MAX_OUTPUT),
+            man: false,
+        };
+
+        let output = get_command_text(&command).ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        Some(
+            output
+                .text
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .take(VERSION_MAX_LINES)
+                .collect::<Vec<&str>>()
+                .join("\n"),
+        )
+    })
+}
+
+// Hash a file with 64 bit FNV-1a, it is stable across builds unlike the std hasher
+fn hash_file(path: &Path) -> io::Result<String> {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut file = File::open(path)?;
+    let mut buffer = [0u8; 64 * 1024];
+    let mut hash = OFFSET_BASIS;
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        for byte in &buffer[..read] {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+
+    Ok(format!("{:016x}", hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn write_binary(name: &str, contents: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("searchhelp-binary-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    // Move the modification time an hour ahead, so it differs from the one stored
+    fn touch(path: &Path) {
+        let modified = SystemTime::now() + Duration::from_secs(3600);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn hashes_files_with_fnv_1a() {
+        assert_eq!(
+            hash_file(&write_binary("empty", "")).unwrap(),
+            "cbf29ce484222325"
+        );
+        assert_eq!(
+            hash_file(&write_binary("a", "a")).unwrap(),
+            "af63dc4c8601ec8c"
+        );
+    }
+
+    #[test]
+    fn compares_touched_binaries_by_their_contents() {
+        let path = write_binary("tool", "binary");
+        let stored = inspect_binary(&path).unwrap();
+        assert!(binary_unchanged(&stored, &path));
+
+        touch(&path);
+        assert!(binary_unchanged(&stored, &path));
+
+        fs::write(&path, "BINARY").unwrap();
+        touch(&path);
+        assert!(!binary_unchanged(&stored, &path));
+
+        let other = write_binary("other", "binary");
+        assert!(!binary_unchanged(&stored, &other));
+    }
+}
@@ -1,10 +1,14 @@
 use std::fmt;
-use std::string::FromUtf8Error;
+use std::process::ExitStatus;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum GetCommandStringError {
     IoError(std::io::Error),
-    FromUtf8Error(FromUtf8Error),
+    NoOutput(ExitStatus),
+    Timeout(Duration),
+    OutputTooLarge(usize),
+    ManPageNotFound(String),
 }
 
 // Implement the Display trait for the GetCommandStringError enum
@@ -12,15 +16,30 @@ impl fmt::Display for GetCommandStringError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             GetCommandStringError::IoError(msg) => write!(f, "{}", msg),
-            GetCommandStringError::FromUtf8Error(code) => write!(f, "{}", code),
+            GetCommandStringError::NoOutput(status) => {
+                write!(f, "nothing was printed to stdout or stderr ({})", status)
+            }
+            GetCommandStringError::Timeout(timeout) => write!(
+                f,
+                "killed after running for {:?}, it may be waiting for something",
+                timeout
+            ),
+            GetCommandStringError::OutputTooLarge(max_output) => {
+                write!(f, "killed after printing more than {} bytes", max_output)
+            }
+            GetCommandStringError::ManPageNotFound(page) => {
+                write!(f, "no man page for {} was found in MANPATH", page)
+            }
         }
     }
 }
 
-// Implement the From trait to convert FromUtf8Error into GetCommandStringError
-impl From<FromUtf8Error> for GetCommandStringError {
-    fn from(err: FromUtf8Error) -> GetCommandStringError {
-        GetCommandStringError::FromUtf8Error(err)
+impl std::error::Error for GetCommandStringError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GetCommandStringError::IoError(err) => err.source(),
+            _ => None,
+        }
     }
 }
 
@@ -30,3 +49,21 @@ impl From<std::io::Error> for GetCommandStringError {
         GetCommandStringError::IoError(err)
     }
 }
+
+#[derive(Debug)]
+pub enum ParseCommandError {
+    UnterminatedQuote(char),
+    TrailingBackslash,
+    EmptyCommand,
+}
+
+// Implement the Display trait for the ParseCommandError enum
+impl fmt::Display for ParseCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCommandError::UnterminatedQuote(quote) => write!(f, "missing closing {}", quote),
+            ParseCommandError::TrailingBackslash => write!(f, "nothing to escape after \\"),
+            ParseCommandError::EmptyCommand => write!(f, "the command is empty"),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::command::errors::ParseCommandError;
+use crate::command::shell_words::{quote_word, split_commands, split_words};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+// Commands ending in this include their stderr in the help text, like in a shell
+const STDERR_REDIRECT: &str = "2>&1";
+
+// Commands like "man:tar" read the man page instead of running a program
+const MAN_PREFIX: &str = "man:";
+
+// Limits for commands that were added without their own
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_MAX_OUTPUT: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HelpCommand {
+    pub argv: Vec<String>,
+    #[serde(default)]
+    pub include_stderr: bool,
+    // Seconds the command may run before it is killed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    // Bytes the command may print on stdout or stderr before it is killed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output: Option<usize>,
+    // Read the man page named by the only argument instead of running a command
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub man: bool,
+}
+
+impl HelpCommand {
+    // Parse a command line into its arguments, in shell mode the line is run by `sh -c`
+    pub fn parse(command: &str, shell: bool) -> Result<Self, ParseCommandError> {
+        if shell {
+            return Ok(HelpCommand {
+                argv: vec!["sh".to_string(), "-c".to_string(), command.to_string()],
+                include_stderr: false,
+                timeout_secs: None,
+                max_output: None,
+                man: false,
+            });
+        }
+
+        let mut argv = split_words(command)?;
+        let include_stderr = argv.last().is_some_and(|a| a == STDERR_REDIRECT);
+        if include_stderr {
+            argv.pop();
+        }
+
+        if argv.is_empty() {
+            return Err(ParseCommandError::EmptyCommand);
+        }
+
+        if let [page] = argv.as_slice() {
+            if let Some(page) = page.strip_prefix(MAN_PREFIX) {
+                return Ok(HelpCommand::man_page(page));
+            }
+        }
+
+        Ok(HelpCommand {
+            argv,
+            include_stderr,
+            timeout_secs: None,
+            max_output: None,
+            man: false,
+        })
+    }
+
+    // The help command reading a man page, e.g. "tar" for tar.1
+    pub fn man_page(page: &str) -> Self {
+        HelpCommand {
+            argv: vec![page.to_string()],
+            include_stderr: false,
+            timeout_secs: None,
+            max_output: None,
+            man: true,
+        }
+    }
+
+    // Parse a list of commands separated by semicolons
+    pub fn parse_list(commands: &str, shell: bool) -> Result<Vec<Self>, ParseCommandError> {
+        split_commands(commands)?
+            .iter()
+            .map(|c| HelpCommand::parse(c, shell))
+            .collect()
+    }
+
+    pub fn is_shell(&self) -> bool {
+        self.argv.len() == 3 && self.argv[0] == "sh" && self.argv[1] == "-c"
+    }
+
+    // Get the help command of a subcommand by inserting its name at `index`, shell
+    // commands and man pages can not be rewritten this way
+    pub fn with_subcommand(&self, index: usize, subcommand: &str) -> Option<Self> {
+        if self.man || self.is_shell() || index == 0 || index > self.argv.len() {
+            return None;
+        }
+
+        let mut argv = self.argv.clone();
+        argv.insert(index, subcommand.to_string());
+
+        Some(HelpCommand {
+            argv,
+            ..self.clone()
+        })
+    }
+
+    // Other ways to get the help text of a subcommand, for when its long help fails, e.g.
+    // "git commit --help" needs man, so "git commit -h" and "git help commit" are tried
+    pub fn subcommand_fallbacks(&self, index: usize, subcommand: &str) -> Vec<Self> {
+        if self.man || self.is_shell() || index == 0 || index > self.argv.len() {
+            return Vec::new();
+        }
+
+        let prefix = &self.argv[..index];
+        [[subcommand, "-h"], ["help", subcommand]]
+            .iter()
+            .map(|args| HelpCommand {
+                argv: prefix
+                    .iter()
+                    .cloned()
+                    .chain(args.iter().map(|a| a.to_string()))
+                    .collect(),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+
+    pub fn max_output(&self) -> usize {
+        self.max_output.unwrap_or(DEFAULT_MAX_OUTPUT)
+    }
+}
+
+// Display the command the way it would be typed in a shell
+impl fmt::Display for HelpCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.man {
+            return write!(f, "{}{}", MAN_PREFIX, quote_word(&self.argv[0]));
+        }
+
+        let line = self
+            .argv
+            .iter()
+            .map(|a| quote_word(a))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        write!(f, "{}", line)?;
+        if self.include_stderr {
+            write!(f, " {}", STDERR_REDIRECT)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_stderr_redirects() {
+        let command = HelpCommand::parse("tool 'sub command' --help 2>&1", false).unwrap();
+        assert_eq!(command.argv, ["tool", "sub command", "--help"]);
+        assert!(command.include_stderr);
+        assert_eq!(command.to_string(), "tool 'sub command' --help 2>&1");
+
+        let command = HelpCommand::parse("tool --help | less", true).unwrap();
+        assert_eq!(command.argv, ["sh", "-c", "tool --help | less"]);
+        assert!(command.is_shell());
+
+        let command = HelpCommand::parse("man:tar", false).unwrap();
+        assert!(command.man);
+        assert_eq!(command.argv, ["tar"]);
+        assert_eq!(command.to_string(), "man:tar");
+
+        assert!(matches!(
+            HelpCommand::parse("2>&1", false),
+            Err(ParseCommandError::EmptyCommand)
+        ));
+    }
+
+    #[test]
+    fn parses_lists_of_commands() {
+        let commands = HelpCommand::parse_list("tool -h; tool help 'a;b'", false).unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[1].argv, ["tool", "help", "a;b"]);
+    }
+
+    #[test]
+    fn inserts_subcommands_before_the_help_arguments() {
+        let command = HelpCommand::parse("git --help", false).unwrap();
+        let subcommand = command.with_subcommand(1, "commit").unwrap();
+        assert_eq!(subcommand.argv, ["git", "commit", "--help"]);
+
+        assert!(command.with_subcommand(0, "commit").is_none());
+        assert!(HelpCommand::parse("git --help", true)
+            .unwrap()
+            .with_subcommand(1, "commit")
+            .is_none());
+    }
+}
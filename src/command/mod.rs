@@ -1,30 +1,372 @@
+pub mod binary;
 pub mod errors;
+pub mod help_command;
+pub mod normalize;
+pub mod shell_words;
+pub mod subcommands;
 
+use crate::db::raw_output::RawOutput;
+use crate::man::{find_page, read_page, roff};
 use errors::GetCommandStringError;
-use std::process::Command;
+use help_command::HelpCommand;
+use normalize::normalize_output;
+use std::fmt;
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-pub fn get_command_texts(command_strings: &Vec<&str>) -> String {
+// How often a running help command is checked for having finished
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+// How long the output readers get past the timeout to read what is left in the pipes
+const READ_GRACE: Duration = Duration::from_millis(200);
+
+#[derive(Debug, PartialEq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+    Both,
+}
+
+impl fmt::Display for OutputStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputStream::Stdout => write!(f, "stdout"),
+            OutputStream::Stderr => write!(f, "stderr"),
+            OutputStream::Both => write!(f, "stdout and stderr"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CommandText {
+    pub text: String,
+    pub stream: OutputStream,
+    pub status: ExitStatus,
+    pub raw: RawOutput,
+}
+
+// The joined help texts of a program's help commands, along with what each of them printed
+#[derive(Debug)]
+pub struct HelpTexts {
+    pub text: String,
+    pub outputs: Vec<(HelpCommand, CommandText)>,
+}
+
+impl HelpTexts {
+    // What the commands printed before cleanup
+    pub fn raw_outputs(&self) -> Vec<RawOutput> {
+        self.outputs
+            .iter()
+            .map(|(_, output)| output.raw.clone())
+            .collect()
+    }
+}
+
+// Run the help commands and join their texts, it fails when none of the commands ran
+pub fn get_command_texts(commands: &[HelpCommand]) -> Result<HelpTexts, GetCommandStringError> {
     let mut texts: String = String::new();
+    let mut outputs = Vec::new();
+    let mut last_error = None;
 
-    for command in command_strings {
+    for command in commands {
         let output = get_command_text(command);
         match output {
             Ok(output) => {
-                texts.push_str(&output);
-                texts.push('\n');
+                texts.push_str(&program_text(command, &output));
+                outputs.push((command.clone(), output));
+            }
+            Err(e) => {
+                eprintln!("Error when executing command: \"{}\": {}", command, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if outputs.is_empty() => Err(e),
+        _ => Ok(HelpTexts {
+            text: texts,
+            outputs,
+        }),
+    }
+}
+
+// The help text a command adds to a program, noting where it came from when it is not plain
+// help output on stdout
+pub fn program_text(command: &HelpCommand, output: &CommandText) -> String {
+    if output.stream == OutputStream::Stdout && output.status.success() {
+        return format!("{}\n", output.text);
+    }
+
+    let source = format!(
+        "help text of \"{}\" from {}, {}",
+        command, output.stream, output.status
+    );
+    eprintln!("Warning: {}", source);
+    format!("[{}]\n{}\n", source, output.text)
+}
+
+pub fn get_command_text(command: &HelpCommand) -> Result<CommandText, GetCommandStringError> {
+    if command.man {
+        return get_man_page_text(command);
+    }
+
+    let timeout = command.timeout();
+    let max_output = command.max_output();
+
+    // Nothing can wait for input, a help command that does is killed by the timeout
+    let mut process = Command::new(&command.argv[0]);
+    process
+        .args(&command.argv[1..])
+        .stdin(Stdio::null())
+        // Keep tools from paging or coloring their output
+        .env("PAGER", "cat")
+        .env("MANPAGER", "cat")
+        .env("GIT_PAGER", "cat")
+        .env("NO_COLOR", "1")
+        .env("TERM", "dumb")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // The processes it starts, like the ones of a shell pipeline, share its process group so
+    // they can be killed along with it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut process, 0);
+    let mut child = process.spawn()?;
+
+    let too_large = Arc::new(AtomicBool::new(false));
+    let stdout_reader = read_limited(child.stdout.take(), max_output, too_large.clone());
+    let stderr_reader = read_limited(child.stderr.take(), max_output, too_large.clone());
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            // Processes left running in the background would keep the output open
+            kill_process_group(&child);
+            break Some(status);
+        }
+
+        if too_large.load(Ordering::Relaxed) || started.elapsed() >= timeout {
+            kill_process_group(&child);
+            child.kill()?;
+            child.wait()?;
+            break None;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    // A process that left the group, like a daemon the command started, can keep the output
+    // open, so the readers are only waited for until the timeout
+    let deadline = (started + timeout).max(Instant::now() + READ_GRACE);
+    let stdout = join_reader(stdout_reader, deadline)?;
+    let stderr = join_reader(stderr_reader, deadline)?;
+
+    if too_large.load(Ordering::Relaxed) {
+        return Err(GetCommandStringError::OutputTooLarge(max_output));
+    }
+    let Some(status) = status else {
+        return Err(GetCommandStringError::Timeout(timeout));
+    };
+
+    let raw = RawOutput {
+        command: command.to_string(),
+        stdout: stdout.clone(),
+        stderr: stderr.clone(),
+    };
+    // Help texts in legacy encodings are kept, with the bytes that are not UTF-8 replaced
+    let stdout = normalize_output(&String::from_utf8_lossy(&stdout));
+    let stderr = normalize_output(&String::from_utf8_lossy(&stderr));
+
+    // Many tools print their usage to stderr, use it when stdout is empty
+    let (text, stream) = if command.include_stderr {
+        (format!("{}{}", stdout, stderr), OutputStream::Both)
+    } else if stdout.trim().is_empty() && !stderr.trim().is_empty() {
+        (stderr, OutputStream::Stderr)
+    } else {
+        (stdout, OutputStream::Stdout)
+    };
+
+    if text.trim().is_empty() {
+        return Err(GetCommandStringError::NoOutput(status));
+    }
+
+    Ok(CommandText {
+        text,
+        stream,
+        status,
+        raw,
+    })
+}
+
+// Render a man page found in MANPATH, so it works without man or groff installed
+fn get_man_page_text(command: &HelpCommand) -> Result<CommandText, GetCommandStringError> {
+    let page = &command.argv[0];
+    let path =
+        find_page(page).ok_or_else(|| GetCommandStringError::ManPageNotFound(page.clone()))?;
+    let source = read_page(&path)?;
+
+    let text = normalize_output(&roff::render(&String::from_utf8_lossy(&source)));
+    if text.trim().is_empty() {
+        return Err(GetCommandStringError::NoOutput(ExitStatus::default()));
+    }
+
+    Ok(CommandText {
+        text,
+        stream: OutputStream::Stdout,
+        status: ExitStatus::default(),
+        raw: RawOutput {
+            command: command.to_string(),
+            stdout: source,
+            stderr: Vec::new(),
+        },
+    })
+}
+
+// Kill the processes a help command started, the readers only finish once all of them are gone
+fn kill_process_group(child: &Child) {
+    // The group may be gone already, so failing is fine
+    #[cfg(unix)]
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = child;
+}
+
+// A stream of the child read on its own thread, what was read so far can be taken while the
+// thread is still waiting for more
+struct Reader {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    handle: JoinHandle<std::io::Result<()>>,
+}
+
+// Read a stream of the child on its own thread, stopping once it goes over `max_output` bytes
+fn read_limited<R: Read + Send + 'static>(
+    stream: Option<R>,
+    max_output: usize,
+    too_large: Arc<AtomicBool>,
+) -> Reader {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let shared = buffer.clone();
+
+    let handle = thread::spawn(move || {
+        let Some(mut stream) = stream else {
+            return Ok(());
+        };
+
+        let mut chunk = [0; 8192];
+        loop {
+            let read = stream.read(&mut chunk)?;
+            if read == 0 {
+                return Ok(());
+            }
+
+            let mut buffer = shared.lock().expect("Output buffer is not poisoned");
+            let room = max_output + 1 - buffer.len();
+            buffer.extend_from_slice(&chunk[..read.min(room)]);
+            if buffer.len() > max_output {
+                too_large.store(true, Ordering::Relaxed);
+                return Ok(());
             }
-            Err(e) => println!("Error when executing command: \"{}\": {}", command, e),
         }
+    });
+
+    Reader { buffer, handle }
+}
+
+// Get what a reader read, a reader still waiting at the deadline is left behind and ends
+// once the pipe closes
+fn join_reader(reader: Reader, deadline: Instant) -> Result<Vec<u8>, GetCommandStringError> {
+    while !reader.handle.is_finished() && Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL);
+    }
+    if reader.handle.is_finished() {
+        reader.handle.join().expect("Output reader panicked")?;
     }
 
-    texts
+    let mut buffer = reader.buffer.lock().expect("Output buffer is not poisoned");
+    Ok(std::mem::take(&mut *buffer))
 }
 
-pub fn get_command_text(command: &str) -> Result<String, GetCommandStringError> {
-    let main_command = command.split_whitespace().next().unwrap().trim();
-    let command_args = command.split_whitespace().skip(1).collect::<Vec<&str>>();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(command: &str) -> Result<CommandText, GetCommandStringError> {
+        get_command_text(&HelpCommand::parse(command, false).unwrap())
+    }
+
+    #[test]
+    fn reads_help_from_stdout() {
+        let output = run("echo 'Usage: tool'").unwrap();
+        assert_eq!(output.text, "Usage: tool\n");
+        assert_eq!(output.stream, OutputStream::Stdout);
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn falls_back_to_stderr_and_keeps_the_exit_status() {
+        let output = run("sh -c 'echo usage: tool >&2; exit 2'").unwrap();
+        assert_eq!(output.text, "usage: tool\n");
+        assert_eq!(output.stream, OutputStream::Stderr);
+        assert_eq!(output.status.code(), Some(2));
+
+        let output = run("sh -c 'echo Usage:; echo Options: >&2' 2>&1").unwrap();
+        assert_eq!(output.text, "Usage:\nOptions:\n");
+        assert_eq!(output.stream, OutputStream::Both);
+    }
+
+    #[test]
+    fn replaces_bytes_that_are_not_utf8() {
+        let output = run("printf 'Usage: caf\\351\\n'").unwrap();
+        assert_eq!(output.text, "Usage: caf\u{fffd}\n");
+        assert_eq!(output.raw.stdout, b"Usage: caf\xe9\n");
+    }
+
+    #[test]
+    fn fails_without_output() {
+        assert!(matches!(
+            run("true"),
+            Err(GetCommandStringError::NoOutput(status)) if status.success()
+        ));
+    }
+
+    #[test]
+    fn kills_commands_that_run_too_long() {
+        let mut command = HelpCommand::parse("sleep 5", false).unwrap();
+        command.timeout_secs = Some(1);
+
+        let started = Instant::now();
+        assert!(matches!(
+            get_command_text(&command),
+            Err(GetCommandStringError::Timeout(timeout)) if timeout == Duration::from_secs(1)
+        ));
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn stops_reading_output_kept_open_by_processes_outside_the_group() {
+        let mut command =
+            HelpCommand::parse("sh -c 'setsid sleep 5 & echo Usage: tool'", false).unwrap();
+        command.timeout_secs = Some(1);
+
+        let started = Instant::now();
+        let output = get_command_text(&command).unwrap();
+        assert_eq!(output.text, "Usage: tool\n");
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
 
-    let output = Command::new(main_command).args(command_args).output()?;
-    let output_str = String::from_utf8(output.stdout)?;
-    Ok(output_str)
+    #[test]
+    fn kills_commands_that_print_too_much() {
+        let mut command = HelpCommand::parse("yes", false).unwrap();
+        command.max_output = Some(1000);
+
+        assert!(matches!(
+            get_command_text(&command),
+            Err(GetCommandStringError::OutputTooLarge(1000))
+        ));
+    }
 }
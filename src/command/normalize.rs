@@ -0,0 +1,82 @@
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{7}';
+
+// Clean up captured help output: drop ANSI escape sequences, resolve backspace overstrikes
+// like "N\bNA\bA" and "_\bN" used by man for bold and underline, and normalize line endings
+pub fn normalize_output(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ESC => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: ends with BEL or with ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == BEL {
+                            break;
+                        }
+                        if c == ESC && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Character set selections like ESC ( B, which `tput sgr0` prints
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                }
+                // Any other escape is a single character
+                _ => {}
+            },
+            // The overstruck character replaces the one before the backspace
+            '\u{8}' => {
+                normalized.pop();
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\t' => normalized.push(c),
+            c if c.is_control() => {}
+            c => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_escape_sequences() {
+        assert_eq!(
+            normalize_output("\u{1b}[1;31m-a\u{1b}[0m, --all\u{1b}(B"),
+            "-a, --all"
+        );
+        assert_eq!(
+            normalize_output("\u{1b}]8;;https://example.com\u{7}link\u{1b}]8;;\u{1b}\\ text"),
+            "link text"
+        );
+    }
+
+    #[test]
+    fn resolves_overstrikes() {
+        assert_eq!(normalize_output("N\u{8}NA\u{8}AM\u{8}ME\u{8}E"), "NAME");
+        assert_eq!(normalize_output("_\u{8}f_\u{8}i_\u{8}l_\u{8}e"), "file");
+        assert_eq!(normalize_output("a\u{8}A"), "A");
+    }
+
+    #[test]
+    fn normalizes_line_endings_and_control_characters() {
+        assert_eq!(normalize_output("Usage:\r\n\ttool\r\n"), "Usage:\n\ttool\n");
+        assert_eq!(normalize_output("bell\u{7} and\u{0} nul"), "bell and nul");
+    }
+}
@@ -0,0 +1,184 @@
+use crate::command::errors::ParseCommandError;
+
+// Split a line into words the way a POSIX shell does, handling quotes and backslashes
+pub fn split_words(line: &str) -> Result<Vec<String>, ParseCommandError> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(ParseCommandError::UnterminatedQuote('\'')),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        // Inside double quotes a backslash only escapes these characters
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(ParseCommandError::UnterminatedQuote('"')),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(ParseCommandError::UnterminatedQuote('"')),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(c) => {
+                    in_word = true;
+                    word.push(c);
+                }
+                None => return Err(ParseCommandError::TrailingBackslash),
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+// Split a list of commands on the semicolons that are not quoted or escaped
+pub fn split_commands(line: &str) -> Result<Vec<String>, ParseCommandError> {
+    let mut commands = Vec::new();
+    let mut command = String::new();
+    let mut quote = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, ';') => {
+                commands.push(std::mem::take(&mut command));
+                continue;
+            }
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '\\') | (Some('"'), '\\') => {
+                command.push(c);
+                match chars.next() {
+                    Some(next) => command.push(next),
+                    None => return Err(ParseCommandError::TrailingBackslash),
+                }
+                continue;
+            }
+            _ => {}
+        }
+        command.push(c);
+    }
+
+    if let Some(q) = quote {
+        return Err(ParseCommandError::UnterminatedQuote(q));
+    }
+    commands.push(command);
+
+    Ok(commands
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect())
+}
+
+// Quote a word so the shell reads it back as the same word
+pub fn quote_word(word: &str) -> String {
+    let is_safe = |c: char| c.is_alphanumeric() || "-_./=:,+@%^".contains(c);
+
+    if !word.is_empty() && word.chars().all(is_safe) {
+        return word.to_string();
+    }
+
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        split_words(line).unwrap()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(words("  tar   --help \t-v "), ["tar", "--help", "-v"]);
+        assert!(words("").is_empty());
+    }
+
+    #[test]
+    fn keeps_quoted_words_together() {
+        assert_eq!(words("tool 'a b' \"c d\""), ["tool", "a b", "c d"]);
+        assert_eq!(words("tool a'b'\"c\""), ["tool", "abc"]);
+        assert_eq!(words("tool '' \"\""), ["tool", "", ""]);
+    }
+
+    #[test]
+    fn handles_backslashes() {
+        assert_eq!(words(r"tool a\ b \'"), ["tool", "a b", "'"]);
+        assert_eq!(words(r#"tool "a\"b" "\n""#), ["tool", "a\"b", r"\n"]);
+        assert_eq!(words(r"tool 'a\b'"), ["tool", r"a\b"]);
+    }
+
+    #[test]
+    fn rejects_unterminated_input() {
+        assert!(matches!(
+            split_words("tool 'a"),
+            Err(ParseCommandError::UnterminatedQuote('\''))
+        ));
+        assert!(matches!(
+            split_words("tool \"a"),
+            Err(ParseCommandError::UnterminatedQuote('"'))
+        ));
+        assert!(matches!(
+            split_words("tool \\"),
+            Err(ParseCommandError::TrailingBackslash)
+        ));
+    }
+
+    #[test]
+    fn splits_commands_on_unquoted_semicolons() {
+        assert_eq!(
+            split_commands(r#"tool -h; tool 'a;b' ; tool "c;d"; tool e\;f;"#).unwrap(),
+            ["tool -h", "tool 'a;b'", "tool \"c;d\"", r"tool e\;f"]
+        );
+        assert!(matches!(
+            split_commands("tool 'a; b"),
+            Err(ParseCommandError::UnterminatedQuote('\''))
+        ));
+    }
+
+    #[test]
+    fn quoted_words_read_back_the_same() {
+        for word in ["--help", "a b", "it's", "", "$HOME", "x;y"] {
+            assert_eq!(words(&quote_word(word)), [word]);
+        }
+        assert_eq!(quote_word("--help"), "--help");
+    }
+}
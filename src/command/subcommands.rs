@@ -0,0 +1,203 @@
+use crate::command::{
+    get_command_text, help_command::HelpCommand, program_text, CommandText, OutputStream,
+};
+use regex::Regex;
+
+#[derive(Debug)]
+pub struct SubcommandText {
+    // The subcommands leading to this one, e.g. ["remote", "add"] for "git remote add"
+    pub path: Vec<String>,
+    pub command: HelpCommand,
+    pub text: String,
+}
+
+// Find the subcommands listed in help text under headings like "Commands:" or
+// "Available Commands:", skipping the group titles git puts between them
+pub fn find_subcommands(help_text: &str) -> Vec<String> {
+    let entry_regex = Regex::new(r"^\s+([a-z][\w-]*)(?:,\s*[\w-]+)*(?:\s{2,}|\t)\S")
+        .expect("Subcommand pattern is valid");
+
+    let mut subcommands: Vec<String> = Vec::new();
+    let mut in_list = false;
+
+    for line in help_text.lines() {
+        let trimmed = line.trim();
+        let is_heading = !line.starts_with(char::is_whitespace) && trimmed.ends_with(':');
+
+        if is_heading {
+            in_list = trimmed.to_lowercase().contains("command");
+            continue;
+        }
+        if !in_list {
+            continue;
+        }
+
+        if let Some(captures) = entry_regex.captures(line) {
+            let name = captures[1].to_string();
+            if name != "help" && !subcommands.contains(&name) {
+                subcommands.push(name);
+            }
+        }
+    }
+
+    subcommands
+}
+
+// Run the help command of every subcommand listed in the help text, and of theirs in turn,
+// until `depth` levels below the program
+pub fn get_subcommand_texts(
+    command: &HelpCommand,
+    help_text: &str,
+    depth: usize,
+) -> Vec<SubcommandText> {
+    let mut texts = Vec::new();
+    crawl_subcommands(command, &[], help_text, depth, &mut texts);
+    texts
+}
+
+fn crawl_subcommands(
+    command: &HelpCommand,
+    path: &[String],
+    help_text: &str,
+    depth: usize,
+    texts: &mut Vec<SubcommandText>,
+) {
+    if depth == 0 {
+        return;
+    }
+
+    for subcommand in find_subcommands(help_text) {
+        // The subcommand goes after the program and the subcommands before it, so
+        // "git --help" becomes "git commit --help"
+        let Some(sub_command) = command.with_subcommand(path.len() + 1, &subcommand) else {
+            return;
+        };
+
+        let fallbacks = command.subcommand_fallbacks(path.len() + 1, &subcommand);
+        let mut sub_path = path.to_vec();
+        sub_path.push(subcommand);
+
+        let Some((used_command, output)) = run_subcommand_help(&sub_command, &fallbacks) else {
+            eprintln!(
+                "Skipped \"{}\", none of its help commands succeeded",
+                sub_command
+            );
+            continue;
+        };
+
+        // Stored like the help text of a program, so refreshing it finds no change
+        let text = program_text(&used_command, &output);
+
+        // Parents go before their children so they can be stored first
+        texts.push(SubcommandText {
+            path: sub_path.clone(),
+            command: used_command,
+            text: text.clone(),
+        });
+        // Children are found through the long help command, the fallbacks can not be extended
+        crawl_subcommands(&sub_command, &sub_path, &text, depth - 1, texts);
+    }
+}
+
+// Run the help command of a subcommand, then its fallbacks until one succeeds. Output of a
+// failed run is only used when it looks like help, like the usage "git commit -h" prints
+// before exiting with 129, and not like the error "git commit --help" prints without man
+fn run_subcommand_help(
+    command: &HelpCommand,
+    fallbacks: &[HelpCommand],
+) -> Option<(HelpCommand, CommandText)> {
+    let mut failed = None;
+
+    for command in std::iter::once(command).chain(fallbacks) {
+        match get_command_text(command) {
+            Ok(output) if output.status.success() => return Some((command.clone(), output)),
+            Ok(output) => {
+                if failed.is_none() && looks_like_help(&output) {
+                    failed = Some((command.clone(), output));
+                }
+            }
+            Err(e) => eprintln!("Error when executing command: \"{}\": {}", command, e),
+        }
+    }
+
+    failed
+}
+
+// Errors are printed to stderr, so only help on stdout or starting with a usage line is kept
+fn looks_like_help(output: &CommandText) -> bool {
+    output.stream != OutputStream::Stderr
+        || output.text.trim_start().to_lowercase().starts_with("usage")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::raw_output::RawOutput;
+    use std::process::ExitStatus;
+
+    fn output(text: &str, stream: OutputStream) -> CommandText {
+        CommandText {
+            text: text.to_string(),
+            stream,
+            status: ExitStatus::default(),
+            raw: RawOutput {
+                command: String::new(),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn finds_subcommands_below_command_headings() {
+        let help = "\
+usage: git [--version] <command>
+
+These are common Git commands:
+
+start a working area
+   clone     Clone a repository
+   init      Create an empty repository
+
+Options:
+   verbose   Not a command
+Available Commands:
+  run, r      Run it
+  help        Help about any command
+  clone       Listed twice
+";
+        assert_eq!(find_subcommands(help), ["clone", "init", "run"]);
+    }
+
+    #[test]
+    fn keeps_only_output_that_looks_like_help() {
+        assert!(looks_like_help(&output(
+            "Options: ...",
+            OutputStream::Stdout
+        )));
+        assert!(looks_like_help(&output(
+            "  Usage: git commit",
+            OutputStream::Stderr
+        )));
+        assert!(!looks_like_help(&output(
+            "fatal: not a git repository",
+            OutputStream::Stderr
+        )));
+    }
+
+    #[test]
+    fn builds_fallback_help_commands() {
+        let command = HelpCommand::parse("git --help", false).unwrap();
+        let fallbacks: Vec<String> = command
+            .subcommand_fallbacks(1, "commit")
+            .iter()
+            .map(HelpCommand::to_string)
+            .collect();
+
+        assert_eq!(fallbacks, ["git commit -h", "git help commit"]);
+        assert!(HelpCommand::parse("man:git", false)
+            .unwrap()
+            .subcommand_fallbacks(1, "commit")
+            .is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use rusqlite::{params, Connection, OptionalExtension, Result};
+
+// Another name a program can be referred to by, e.g. "k" for "kubectl"
+#[derive(Debug)]
+pub struct Alias {
+    pub alias: String,
+    pub program_id: i32,
+}
+
+impl Alias {
+    // Create the alias table
+    pub fn create_table(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS alias (
+                alias         TEXT PRIMARY KEY,
+                program_id    INTEGER NOT NULL REFERENCES program (id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    // Insert the alias into the database
+    pub fn insert(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "INSERT INTO alias (alias, program_id) VALUES (?1, ?2)",
+            params![self.alias, self.program_id],
+        )?;
+        Ok(())
+    }
+
+    // Get the program ID an alias refers to
+    pub fn get_program_id(conn: &Connection, alias: &str) -> Result<Option<i32>> {
+        conn.query_row(
+            "SELECT program_id FROM alias WHERE alias = ?1",
+            params![alias],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    // Get the aliases of a program, sorted by name
+    pub fn get_by_program(conn: &Connection, program_id: i32) -> Result<Vec<String>> {
+        let mut stmt =
+            conn.prepare("SELECT alias FROM alias WHERE program_id = ?1 ORDER BY alias")?;
+        let alias_iter = stmt.query_map([program_id], |row| row.get(0))?;
+        alias_iter.collect()
+    }
+
+    // Get all aliases, sorted by name
+    pub fn get_all(conn: &Connection) -> Result<Vec<Alias>> {
+        let mut stmt = conn.prepare("SELECT alias, program_id FROM alias ORDER BY alias")?;
+        let alias_iter = stmt.query_map([], |row| {
+            Ok(Alias {
+                alias: row.get(0)?,
+                program_id: row.get(1)?,
+            })
+        })?;
+        alias_iter.collect()
+    }
+
+    // Delete an alias, returns whether it existed
+    pub fn delete(conn: &Connection, alias: &str) -> Result<bool> {
+        let deleted = conn.execute("DELETE FROM alias WHERE alias = ?1", params![alias])?;
+        Ok(deleted > 0)
+    }
+}
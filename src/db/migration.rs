@@ -0,0 +1,357 @@
+use crate::command::help_command::HelpCommand;
+use crate::db::{
+    alias::Alias, program::Program, program_binary::ProgramBinary, program_command::ProgramCommand,
+    program_option::ProgramOption, program_revision::ProgramRevision, program_tag::ProgramTag,
+    raw_output::RawOutput, search_index::create_index, section::Section,
+};
+use rusqlite::{params, Connection, Transaction};
+use std::fmt;
+
+// A step from one schema version to the next, the database is at version N once the first
+// N migrations are applied
+struct Migration {
+    description: &'static str,
+    apply: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+// Never change or reorder a migration that was released, add a new one instead
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "create the tables",
+        apply: create_tables,
+    },
+    Migration {
+        description: "move help commands to the program_command table",
+        apply: move_help_commands,
+    },
+    Migration {
+        description: "make program names unique",
+        apply: unique_program_names,
+    },
+    Migration {
+        description: "create the alias table",
+        apply: create_aliases,
+    },
+    Migration {
+        description: "create the program_tag table",
+        apply: create_tags,
+    },
+    Migration {
+        description: "split the text after usage lines into its own section",
+        apply: reparse_sections,
+    },
+];
+
+// The schema version this build works with
+pub const SCHEMA_VERSION: usize = MIGRATIONS.len();
+
+#[derive(Debug)]
+pub enum MigrationError {
+    SqliteError(rusqlite::Error),
+    // The database was migrated by a newer build, which may have changed it in ways this
+    // build does not know about
+    NewerDatabase(usize),
+    Failed(usize, &'static str, rusqlite::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::SqliteError(e) => write!(f, "{}", e),
+            MigrationError::NewerDatabase(version) => write!(
+                f,
+                "the database has schema version {}, but this version of searchhelp only supports up to {}, please upgrade searchhelp",
+                version, SCHEMA_VERSION
+            ),
+            MigrationError::Failed(version, description, e) => write!(
+                f,
+                "migration {} ({}) failed, the database was left unchanged: {}",
+                version, description, e
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MigrationError::SqliteError(err) | MigrationError::Failed(_, _, err) => err.source(),
+            MigrationError::NewerDatabase(_) => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(error: rusqlite::Error) -> Self {
+        MigrationError::SqliteError(error)
+    }
+}
+
+// Bring the database up to the schema version of this build, the pending migrations are
+// applied in one transaction so a failure leaves the database as it was
+pub fn migrate(conn: &mut Connection) -> Result<(), MigrationError> {
+    let version = schema_version(conn)?;
+    if version > SCHEMA_VERSION {
+        return Err(MigrationError::NewerDatabase(version));
+    }
+    if version == SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        (migration.apply)(&tx)
+            .map_err(|e| MigrationError::Failed(index + 1, migration.description, e))?;
+    }
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn schema_version(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+// Databases from before migrations may have any of the tables, the tables are only created
+// when they are missing and filled in from the stored help texts
+fn create_tables(tx: &Transaction) -> rusqlite::Result<()> {
+    Program::create_table(tx)?;
+    ProgramOption::create_table(tx)?;
+    RawOutput::create_table(tx)?;
+    Section::create_table(tx)?;
+    ProgramRevision::create_table(tx)?;
+    ProgramBinary::create_table(tx)?;
+    create_index(tx)
+}
+
+// The help commands were stored in the program table, as a JSON list or in older databases
+// as a single string separated by semicolons
+fn move_help_commands(tx: &Transaction) -> rusqlite::Result<()> {
+    ProgramCommand::create_table(tx)?;
+
+    let mut stmt = tx.prepare("SELECT id, help_command FROM program")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<(i32, String)>>>()?;
+
+    for (id, help_command) in rows {
+        let commands = if help_command.starts_with('[') {
+            serde_json::from_str(&help_command)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?
+        } else {
+            HelpCommand::parse_list(&help_command, false)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.to_string().into()))?
+        };
+        ProgramCommand::replace_for_program(tx, id, &commands)?;
+    }
+
+    tx.execute("ALTER TABLE program DROP COLUMN help_command", [])?;
+    Ok(())
+}
+
+// Programs that were added twice under the same name are merged into the one added last,
+// the older ones keep their help texts as earlier revisions of it
+fn unique_program_names(tx: &Transaction) -> rusqlite::Result<()> {
+    let mut stmt = tx
+        .prepare("SELECT name, MAX(id), COUNT(*) FROM program GROUP BY name HAVING COUNT(*) > 1")?;
+    let duplicates = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i32>(1)?,
+                row.get::<_, usize>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<(String, i32, usize)>>>()?;
+
+    for (name, kept_id, count) in duplicates {
+        // Subcommands are moved first, so they are merged by their own names instead of
+        // being deleted along with their parent
+        tx.execute(
+            "UPDATE program SET parent_id = ?1
+             WHERE parent_id IN (SELECT id FROM program WHERE name = ?2 AND id != ?1)",
+            params![kept_id, name],
+        )?;
+        tx.execute(
+            "UPDATE program_revision SET program_id = ?1
+             WHERE program_id IN (SELECT id FROM program WHERE name = ?2 AND id != ?1)",
+            params![kept_id, name],
+        )?;
+        tx.execute(
+            "DELETE FROM program WHERE name = ?1 AND id != ?2",
+            params![name, kept_id],
+        )?;
+        eprintln!(
+            "Merged {} programs named \"{}\" into the one added last",
+            count, name
+        );
+    }
+
+    tx.execute("CREATE UNIQUE INDEX program_name ON program (name)", [])?;
+    Ok(())
+}
+
+fn create_aliases(tx: &Transaction) -> rusqlite::Result<()> {
+    Alias::create_table(tx)
+}
+
+fn create_tags(tx: &Transaction) -> rusqlite::Result<()> {
+    ProgramTag::create_table(tx)
+}
+
+// Sections are stored when a program is added, so stored ones are parsed again
+fn reparse_sections(tx: &Transaction) -> rusqlite::Result<()> {
+    for (id, help_text) in Program::get_help_texts(tx)? {
+        Section::replace_for_program(tx, id, &help_text)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A database from before migrations, with help commands in the program table and no
+    // option or section tables
+    fn legacy_database(programs: &[(&str, &str, &str)]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", true).unwrap();
+        conn.execute(
+            "CREATE TABLE program (
+                id            INTEGER PRIMARY KEY AUTOINCREMENT,
+                name          TEXT NOT NULL,
+                help_command  TEXT NOT NULL,
+                help_text     TEXT NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+        for (name, help_command, help_text) in programs {
+            conn.execute(
+                "INSERT INTO program (name, help_command, help_text) VALUES (?1, ?2, ?3)",
+                params![name, help_command, help_text],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn commands(conn: &Connection, program_id: i32) -> Vec<String> {
+        ProgramCommand::get_by_program(conn, program_id)
+            .unwrap()
+            .iter()
+            .map(HelpCommand::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn moves_legacy_and_json_help_commands() {
+        let json =
+            serde_json::to_string(&[HelpCommand::parse("ls --help", false).unwrap()]).unwrap();
+        let mut conn = legacy_database(&[
+            ("tar", "tar --help; tar --usage 2>&1", "Usage: tar\n"),
+            ("ls", &json, "Usage: ls\n"),
+        ]);
+
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(commands(&conn, 1), ["tar --help", "tar --usage 2>&1"]);
+        assert_eq!(commands(&conn, 2), ["ls --help"]);
+        let has_column: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM pragma_table_info('program')
+                 WHERE name = 'help_command')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!has_column);
+    }
+
+    #[test]
+    fn fills_in_options_and_sections_of_stored_programs() {
+        let mut conn = legacy_database(&[(
+            "ls",
+            "ls --help",
+            "Usage: ls [OPTION]...\n\nOptions:\n  -a, --all  do not ignore entries\n",
+        )]);
+
+        migrate(&mut conn).unwrap();
+
+        let options = ProgramOption::get_by_program(&conn, 1).unwrap();
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].long_flag.as_deref(), Some("--all"));
+        let sections = Section::get_by_program(&conn, 1).unwrap();
+        assert_eq!(
+            sections
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<&str>>(),
+            ["Usage", "Options"]
+        );
+    }
+
+    #[test]
+    fn migrating_again_changes_nothing() {
+        let mut conn = legacy_database(&[(
+            "ls",
+            "ls --help",
+            "Usage: ls\n  -a, --all  do not ignore entries\n",
+        )]);
+
+        migrate(&mut conn).unwrap();
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(commands(&conn, 1), ["ls --help"]);
+        assert_eq!(ProgramOption::get_by_program(&conn, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn merges_programs_with_the_same_name_into_the_last_one() {
+        let mut conn = legacy_database(&[
+            ("tar", "tar --help", "Usage: tar (old)\n"),
+            ("tar create", "tar create --help", "Usage: tar create\n"),
+            ("tar", "tar -h", "Usage: tar (new)\n"),
+        ]);
+        conn.execute("ALTER TABLE program ADD COLUMN parent_id INTEGER", [])
+            .unwrap();
+        conn.execute("UPDATE program SET parent_id = 1 WHERE id = 2", [])
+            .unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let tar = Program::get_by_name(&conn, "tar").unwrap().unwrap();
+        assert_eq!(tar.id, Some(3));
+        assert_eq!(commands(&conn, 3), ["tar -h"]);
+        assert!(commands(&conn, 1).is_empty());
+        let revisions = ProgramRevision::get_by_program(&conn, 3).unwrap();
+        assert_eq!(
+            revisions
+                .iter()
+                .map(|r| r.help_text.as_str())
+                .collect::<Vec<&str>>(),
+            ["Usage: tar (old)\n", "Usage: tar (new)\n"]
+        );
+        let create = Program::get_by_name(&conn, "tar create").unwrap().unwrap();
+        assert_eq!(create.parent_id, Some(3));
+    }
+
+    #[test]
+    fn refuses_databases_of_newer_versions() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+
+        let result = migrate(&mut conn);
+
+        assert!(matches!(result, Err(MigrationError::NewerDatabase(v)) if v == SCHEMA_VERSION + 1));
+        let tables: usize = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+}
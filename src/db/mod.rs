@@ -1,8 +1,96 @@
+pub mod alias;
+pub mod migration;
 pub mod program;
+pub mod program_binary;
+pub mod program_command;
+pub mod program_option;
+pub mod program_revision;
+pub mod program_summary;
+pub mod program_tag;
+pub mod raw_output;
+pub mod search_index;
+pub mod section;
 
 use rusqlite::{Connection, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Environment variable that overrides where the database is stored
+pub const DATABASE_ENV: &str = "SEARCHHELP_DB";
+// Databases used to be stored in the current directory under this name
+pub const LEGACY_DATABASE_FILE: &str = "search_help.sqlite";
+const DATABASE_DIR: &str = "searchhelp";
+const DATABASE_FILE: &str = "searchhelp.sqlite";
+
+static DATABASE_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+// The database in the user's data directory, e.g. ~/.local/share/searchhelp/searchhelp.sqlite
+pub fn default_database_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(DATABASE_DIR).join(DATABASE_FILE))
+}
+
+// Use the database at a path for all connections, set once before the first one is opened
+pub fn set_database_path(path: PathBuf) {
+    DATABASE_PATH
+        .set(path)
+        .expect("Database path is only set once");
+}
+
+// Move a database file, across file systems it is copied and removed
+pub fn move_database(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
 
 // Open a connection to the SQLite database file
 pub fn open_connection() -> Result<Connection> {
-    Connection::open("search_help.sqlite")
+    let path = DATABASE_PATH
+        .get()
+        .map(PathBuf::as_path)
+        .unwrap_or(Path::new(LEGACY_DATABASE_FILE));
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    Ok(conn)
+}
+
+// An empty in-memory database at the current schema version, for tests
+#[cfg(test)]
+pub fn open_test_connection() -> Connection {
+    let mut conn = Connection::open_in_memory().expect("In-memory database opens");
+    conn.pragma_update(None, "foreign_keys", true)
+        .expect("Foreign keys can be enabled");
+    migration::migrate(&mut conn).expect("Empty database is migrated");
+    conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_the_database_in_its_own_data_directory() {
+        if let Some(path) = default_database_path() {
+            assert!(path.ends_with("searchhelp/searchhelp.sqlite"));
+        }
+    }
+
+    #[test]
+    fn moves_database_files() {
+        let dir = std::env::temp_dir().join(format!("searchhelp-move-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let from = dir.join(LEGACY_DATABASE_FILE);
+        let to = dir.join(DATABASE_FILE);
+        fs::write(&from, "database").unwrap();
+
+        move_database(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "database");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,89 +1,105 @@
-use rusqlite::{params, Connection, Result};
+use crate::command::help_command::HelpCommand;
+use crate::db::{
+    alias::Alias, program_command::ProgramCommand, program_option::ProgramOption,
+    program_revision::ProgramRevision, section::Section,
+};
+use rusqlite::{params, Connection, Result, Row};
 
 #[derive(Debug)]
 pub struct Program {
     pub id: Option<i32>,
     pub name: String,
-    pub commands: Vec<String>,
+    pub commands: Vec<HelpCommand>,
     pub help_text: String,
+    // Set for subcommands, e.g. "git commit" has "git" as its parent
+    pub parent_id: Option<i32>,
 }
 
 impl Program {
     // Constructor for creating a new Program instance
-    pub fn new(name: &str, help_command: Vec<String>, help_text: &str) -> Self {
+    pub fn new(name: &str, help_command: Vec<HelpCommand>, help_text: &str) -> Self {
         Program {
             id: None,
             name: name.to_string(),
             commands: help_command.clone(),
             help_text: help_text.to_string(),
+            parent_id: None,
         }
     }
 
-    // Create the program table
+    // Create the program table as it was at schema version 1, later changes are migrations
     pub fn create_table(conn: &Connection) -> Result<()> {
         conn.execute(
             "CREATE TABLE IF NOT EXISTS program (
                 id            INTEGER PRIMARY KEY AUTOINCREMENT,
                 name          TEXT NOT NULL,
                 help_command  TEXT NOT NULL,
-                help_text     TEXT NOT NULL
+                help_text     TEXT NOT NULL,
+                parent_id     INTEGER REFERENCES program (id) ON DELETE CASCADE
             )",
             [],
         )?;
+
+        // Databases created before subcommands were stored lack the parent column
+        let has_parent: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info('program') WHERE name = 'parent_id')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_parent {
+            conn.execute(
+                "ALTER TABLE program ADD COLUMN parent_id INTEGER REFERENCES program (id) ON DELETE CASCADE",
+                [],
+            )?;
+        }
+
         Ok(())
     }
 
-    // Insert a new program into the database
-    pub fn insert(&self, conn: &Connection) -> Result<()> {
+    // Insert a new program into the database and return its ID
+    pub fn insert(&self, conn: &Connection) -> Result<i32> {
         conn.execute(
-            "INSERT INTO program (name, help_command, help_text) VALUES (?1, ?2, ?3)",
-            params![self.name, self.commands.join(";"), self.help_text],
+            "INSERT INTO program (name, help_text, parent_id) VALUES (?1, ?2, ?3)",
+            params![self.name, self.help_text, self.parent_id],
         )?;
-        Ok(())
+        let id = conn.last_insert_rowid() as i32;
+        ProgramCommand::replace_for_program(conn, id, &self.commands)?;
+        ProgramOption::replace_for_program(conn, id, &self.help_text)?;
+        Section::replace_for_program(conn, id, &self.help_text)?;
+        ProgramRevision::record(conn, id, &self.help_text)?;
+        Ok(id)
     }
 
     pub fn get_all(conn: &Connection) -> Result<Vec<Program>> {
-        let mut stmt = conn.prepare("SELECT * FROM program")?;
-        let program_iter = stmt.query_map([], |row| {
-            Ok(Program {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                commands: row
-                    .get::<_, String>(2)?
-                    .split(';')
-                    .map(|s| s.trim().to_string())
-                    .collect::<Vec<String>>(),
-                help_text: row.get(3)?,
-            })
-        })?;
+        let mut stmt = conn.prepare("SELECT id, name, help_text, parent_id FROM program")?;
+        let program_iter = stmt.query_map([], Program::from_row)?;
+        let mut commands = ProgramCommand::get_all(conn)?;
 
         let mut programs = Vec::new();
         for program in program_iter {
-            programs.push(program?);
+            let mut program = program?;
+            program.commands = commands.remove(&program.id.unwrap()).unwrap_or_default();
+            programs.push(program);
         }
 
         Ok(programs)
     }
 
+    // Get the ID and help text of all programs, without loading anything else
+    pub fn get_help_texts(conn: &Connection) -> Result<Vec<(i32, String)>> {
+        let mut stmt = conn.prepare("SELECT id, help_text FROM program")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
     // Get a program by ID
     pub fn get_by_id(conn: &Connection, id: i32) -> Result<Option<Program>> {
         let mut stmt =
-            conn.prepare("SELECT id, name, help_command, help_text FROM program WHERE id = ?1")?;
-        let program_iter = stmt.query_map([id], |row| {
-            Ok(Program {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                commands: row
-                    .get::<_, String>(2)?
-                    .split(';')
-                    .map(|s| s.trim().to_string())
-                    .collect::<Vec<String>>(),
-                help_text: row.get(3)?,
-            })
-        })?;
+            conn.prepare("SELECT id, name, help_text, parent_id FROM program WHERE id = ?1")?;
+        let mut program_iter = stmt.query_map([id], Program::from_row)?;
 
-        for program in program_iter {
-            return Ok(Some(program?));
+        if let Some(program) = program_iter.next() {
+            return Program::with_commands(conn, program?).map(Some);
         }
 
         Ok(None)
@@ -92,44 +108,87 @@ impl Program {
     // Get a program by name
     pub fn get_by_name(conn: &Connection, name: &str) -> Result<Option<Program>> {
         let mut stmt =
-            conn.prepare("SELECT id, name, help_command, help_text FROM program WHERE name = ?1")?;
-        let program_iter = stmt.query_map([name], |row| {
-            Ok(Program {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                commands: row
-                    .get::<_, String>(2)?
-                    .split(';')
-                    .map(|s| s.trim().to_string())
-                    .collect::<Vec<String>>(),
-                help_text: row.get(3)?,
-            })
-        })?;
+            conn.prepare("SELECT id, name, help_text, parent_id FROM program WHERE name = ?1")?;
+        let mut program_iter = stmt.query_map([name], Program::from_row)?;
 
-        for program in program_iter {
-            return Ok(Some(program?));
+        if let Some(program) = program_iter.next() {
+            return Program::with_commands(conn, program?).map(Some);
         }
 
         Ok(None)
     }
 
+    // Get a program by its name or one of its aliases, names come first
+    pub fn get_by_name_or_alias(conn: &Connection, name: &str) -> Result<Option<Program>> {
+        if let Some(program) = Program::get_by_name(conn, name)? {
+            return Ok(Some(program));
+        }
+
+        match Alias::get_program_id(conn, name)? {
+            Some(id) => Program::get_by_id(conn, id),
+            None => Ok(None),
+        }
+    }
+
+    // Get the IDs of all subcommands below a program, at any depth
+    pub fn get_descendant_ids(conn: &Connection, id: i32) -> Result<Vec<i32>> {
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE descendant (id) AS (
+                SELECT id FROM program WHERE parent_id = ?1
+                UNION
+                SELECT program.id FROM program JOIN descendant ON program.parent_id = descendant.id
+            )
+            SELECT id FROM descendant",
+        )?;
+        let id_iter = stmt.query_map([id], |row| row.get(0))?;
+
+        let mut ids = Vec::new();
+        for id in id_iter {
+            ids.push(id?);
+        }
+
+        Ok(ids)
+    }
+
     // Update a program's details in the database
     pub fn update(&self, conn: &Connection) -> Result<()> {
         conn.execute(
-            "UPDATE program SET name = ?1, help_command = ?2, help_text = ?3 WHERE id = ?4",
-            params![
-                self.name,
-                self.commands.join(";"),
-                self.help_text,
-                self.id.unwrap()
-            ],
+            "UPDATE program SET name = ?1, help_text = ?2 WHERE id = ?3",
+            params![self.name, self.help_text, self.id.unwrap()],
         )?;
+        ProgramCommand::replace_for_program(conn, self.id.unwrap(), &self.commands)?;
+        ProgramOption::replace_for_program(conn, self.id.unwrap(), &self.help_text)?;
+        Section::replace_for_program(conn, self.id.unwrap(), &self.help_text)?;
+        ProgramRevision::record(conn, self.id.unwrap(), &self.help_text)?;
         Ok(())
     }
 
-    // Delete a program by ID
+    // Delete a program by ID, the foreign keys cascade to its subcommands and everything
+    // stored about them
     pub fn delete(conn: &Connection, id: i32) -> Result<()> {
         conn.execute("DELETE FROM program WHERE id = ?1", params![id])?;
         Ok(())
     }
+
+    // Delete the subcommands below a program
+    pub fn delete_children(conn: &Connection, id: i32) -> Result<()> {
+        conn.execute("DELETE FROM program WHERE parent_id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn with_commands(conn: &Connection, mut program: Program) -> Result<Program> {
+        program.commands = ProgramCommand::get_by_program(conn, program.id.unwrap())?;
+        Ok(program)
+    }
+
+    // The commands are in their own table, they are filled in after the row is read
+    fn from_row(row: &Row) -> Result<Program> {
+        Ok(Program {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            commands: Vec::new(),
+            help_text: row.get(2)?,
+            parent_id: row.get(3)?,
+        })
+    }
 }
@@ -0,0 +1,90 @@
+use rusqlite::{params, Connection, OptionalExtension, Result};
+
+// The executable a program's help text was captured from
+#[derive(Debug, Clone)]
+pub struct ProgramBinary {
+    // Absolute path the name resolved to through PATH
+    pub path: String,
+    // Seconds since the Unix epoch
+    pub modified: i64,
+    pub size: u64,
+    // FNV-1a hash of the file, as hex
+    pub hash: String,
+    // What the first of --version, -V and version printed
+    pub version_output: Option<String>,
+    // UTC time the binary was last looked at, set once it is stored
+    pub checked_at: Option<String>,
+}
+
+impl ProgramBinary {
+    // Create the program_binary table
+    pub fn create_table(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS program_binary (
+                program_id      INTEGER PRIMARY KEY REFERENCES program (id) ON DELETE CASCADE,
+                path            TEXT NOT NULL,
+                modified        INTEGER NOT NULL,
+                size            INTEGER NOT NULL,
+                hash            TEXT NOT NULL,
+                version_output  TEXT,
+                checked_at      TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    // Store the binary of a program, replacing the one stored before
+    pub fn replace_for_program(&self, conn: &Connection, program_id: i32) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO program_binary (program_id, path, modified, size, hash, version_output)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                program_id,
+                self.path,
+                self.modified,
+                self.size,
+                self.hash,
+                self.version_output
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Get the binary stored for a program
+    pub fn get_by_program(conn: &Connection, program_id: i32) -> Result<Option<ProgramBinary>> {
+        conn.query_row(
+            "SELECT path, modified, size, hash, version_output, checked_at
+             FROM program_binary WHERE program_id = ?1",
+            params![program_id],
+            |row| {
+                Ok(ProgramBinary {
+                    path: row.get(0)?,
+                    modified: row.get(1)?,
+                    size: row.get(2)?,
+                    hash: row.get(3)?,
+                    version_output: row.get(4)?,
+                    checked_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    pub fn delete_by_program(conn: &Connection, program_id: i32) -> Result<()> {
+        conn.execute(
+            "DELETE FROM program_binary WHERE program_id = ?1",
+            params![program_id],
+        )?;
+        Ok(())
+    }
+
+    // Format the modification time as UTC, like the other timestamps in the database
+    pub fn modified_at(&self, conn: &Connection) -> Result<String> {
+        conn.query_row(
+            "SELECT datetime(?1, 'unixepoch')",
+            params![self.modified],
+            |row| row.get(0),
+        )
+    }
+}
@@ -0,0 +1,113 @@
+use crate::command::help_command::HelpCommand;
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, Error, Result, Row};
+use std::collections::HashMap;
+
+// The help commands of a program, run in order and joined into its help text
+pub struct ProgramCommand;
+
+impl ProgramCommand {
+    // Create the program_command table
+    pub fn create_table(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS program_command (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                program_id      INTEGER NOT NULL REFERENCES program (id) ON DELETE CASCADE,
+                position        INTEGER NOT NULL,
+                argv            TEXT NOT NULL,
+                include_stderr  INTEGER NOT NULL,
+                timeout_secs    INTEGER,
+                max_output      INTEGER,
+                man             INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    // Replace the help commands stored for a program
+    pub fn replace_for_program(
+        conn: &Connection,
+        program_id: i32,
+        commands: &[HelpCommand],
+    ) -> Result<()> {
+        ProgramCommand::delete_by_program(conn, program_id)?;
+        for (position, command) in commands.iter().enumerate() {
+            // The arguments are stored as a JSON array, they may contain any character
+            let argv = serde_json::to_string(&command.argv)
+                .map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))?;
+            conn.execute(
+                "INSERT INTO program_command (program_id, position, argv, include_stderr, timeout_secs, max_output, man)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    program_id,
+                    position,
+                    argv,
+                    command.include_stderr,
+                    command.timeout_secs,
+                    command.max_output,
+                    command.man
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    // Get the help commands of a program, in the order they are run
+    pub fn get_by_program(conn: &Connection, program_id: i32) -> Result<Vec<HelpCommand>> {
+        let mut stmt = conn.prepare(
+            "SELECT program_id, argv, include_stderr, timeout_secs, max_output, man
+             FROM program_command WHERE program_id = ?1 ORDER BY position",
+        )?;
+        let command_iter = stmt.query_map([program_id], ProgramCommand::from_row)?;
+
+        let mut commands = Vec::new();
+        for command in command_iter {
+            commands.push(command?.1);
+        }
+
+        Ok(commands)
+    }
+
+    // Get the help commands of all programs, by program ID
+    pub fn get_all(conn: &Connection) -> Result<HashMap<i32, Vec<HelpCommand>>> {
+        let mut stmt = conn.prepare(
+            "SELECT program_id, argv, include_stderr, timeout_secs, max_output, man
+             FROM program_command ORDER BY program_id, position",
+        )?;
+        let command_iter = stmt.query_map([], ProgramCommand::from_row)?;
+
+        let mut commands: HashMap<i32, Vec<HelpCommand>> = HashMap::new();
+        for command in command_iter {
+            let (program_id, command) = command?;
+            commands.entry(program_id).or_default().push(command);
+        }
+
+        Ok(commands)
+    }
+
+    // Delete the help commands of a program
+    pub fn delete_by_program(conn: &Connection, program_id: i32) -> Result<()> {
+        conn.execute(
+            "DELETE FROM program_command WHERE program_id = ?1",
+            params![program_id],
+        )?;
+        Ok(())
+    }
+
+    fn from_row(row: &Row) -> Result<(i32, HelpCommand)> {
+        let argv = serde_json::from_str(&row.get::<_, String>(1)?)
+            .map_err(|e| Error::FromSqlConversionFailure(1, Type::Text, Box::new(e)))?;
+
+        Ok((
+            row.get(0)?,
+            HelpCommand {
+                argv,
+                include_stderr: row.get(2)?,
+                timeout_secs: row.get(3)?,
+                max_output: row.get(4)?,
+                man: row.get(5)?,
+            },
+        ))
+    }
+}
@@ -0,0 +1,124 @@
+use crate::db::program::Program;
+use crate::help_parser::parse_options;
+use rusqlite::{params, Connection, Result};
+
+#[derive(Debug, Default)]
+pub struct ProgramOption {
+    pub short_flag: Option<String>,
+    pub long_flag: Option<String>,
+    pub value: Option<String>,
+    pub default_value: Option<String>,
+    pub description: String,
+    pub line_number: usize,
+}
+
+impl ProgramOption {
+    // Create the option table, parsing the options of programs stored before it existed
+    pub fn create_table(conn: &Connection) -> Result<()> {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'option')",
+            [],
+            |row| row.get(0),
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS option (
+                id             INTEGER PRIMARY KEY AUTOINCREMENT,
+                program_id     INTEGER NOT NULL REFERENCES program (id) ON DELETE CASCADE,
+                short_flag     TEXT,
+                long_flag      TEXT,
+                value          TEXT,
+                default_value  TEXT,
+                description    TEXT NOT NULL,
+                line_number    INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        if !exists {
+            for (id, help_text) in Program::get_help_texts(conn)? {
+                ProgramOption::replace_for_program(conn, id, &help_text)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Insert the option for a program into the database
+    pub fn insert(&self, conn: &Connection, program_id: i32) -> Result<()> {
+        conn.execute(
+            "INSERT INTO option (program_id, short_flag, long_flag, value, default_value, description, line_number)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                program_id,
+                self.short_flag,
+                self.long_flag,
+                self.value,
+                self.default_value,
+                self.description,
+                self.line_number
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Get the options of a program in the order they appear in its help text
+    pub fn get_by_program(conn: &Connection, program_id: i32) -> Result<Vec<ProgramOption>> {
+        let mut stmt = conn.prepare(
+            "SELECT short_flag, long_flag, value, default_value, description, line_number
+             FROM option WHERE program_id = ?1 ORDER BY line_number",
+        )?;
+        let option_iter = stmt.query_map([program_id], |row| {
+            Ok(ProgramOption {
+                short_flag: row.get(0)?,
+                long_flag: row.get(1)?,
+                value: row.get(2)?,
+                default_value: row.get(3)?,
+                description: row.get(4)?,
+                line_number: row.get(5)?,
+            })
+        })?;
+
+        let mut options = Vec::new();
+        for option in option_iter {
+            options.push(option?);
+        }
+
+        Ok(options)
+    }
+
+    // Replace the stored options of a program with the ones parsed from its help text
+    pub fn replace_for_program(conn: &Connection, program_id: i32, help_text: &str) -> Result<()> {
+        ProgramOption::delete_by_program(conn, program_id)?;
+        for option in parse_options(help_text) {
+            option.insert(conn, program_id)?;
+        }
+        Ok(())
+    }
+
+    // Delete all options of a program
+    pub fn delete_by_program(conn: &Connection, program_id: i32) -> Result<()> {
+        conn.execute(
+            "DELETE FROM option WHERE program_id = ?1",
+            params![program_id],
+        )?;
+        Ok(())
+    }
+
+    // Format the flags like they are written in help texts, e.g. "-o, --output <FILE>"
+    pub fn flags(&self) -> String {
+        let mut flags = [&self.short_flag, &self.long_flag]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        if let Some(value) = &self.value {
+            flags.push(' ');
+            flags.push_str(value);
+        }
+
+        flags
+    }
+}
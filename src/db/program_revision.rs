@@ -0,0 +1,118 @@
+use crate::db::program::Program;
+use crate::help_parser::version::detect_version;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+
+// A help text captured for a program at some point
+#[derive(Debug)]
+pub struct ProgramRevision {
+    // Revisions are numbered from 1 for each program, oldest first
+    pub number: usize,
+    pub help_text: String,
+    pub version: Option<String>,
+    // UTC time, as "YYYY-MM-DD HH:MM:SS"
+    pub created_at: String,
+}
+
+impl ProgramRevision {
+    // Create the revision table, the current help texts of stored programs become their
+    // first revision
+    pub fn create_table(conn: &Connection) -> Result<()> {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'program_revision')",
+            [],
+            |row| row.get(0),
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS program_revision (
+                id            INTEGER PRIMARY KEY AUTOINCREMENT,
+                program_id    INTEGER NOT NULL REFERENCES program (id) ON DELETE CASCADE,
+                help_text     TEXT NOT NULL,
+                version       TEXT,
+                created_at    TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+            [],
+        )?;
+
+        if !exists {
+            for (id, help_text) in Program::get_help_texts(conn)? {
+                ProgramRevision::record(conn, id, &help_text)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Store a help text as the newest revision of a program, unless it is the newest already
+    pub fn record(conn: &Connection, program_id: i32, help_text: &str) -> Result<()> {
+        let latest: Option<String> = conn
+            .query_row(
+                "SELECT help_text FROM program_revision WHERE program_id = ?1
+                 ORDER BY id DESC LIMIT 1",
+                params![program_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if latest.as_deref() == Some(help_text) {
+            return Ok(());
+        }
+
+        conn.execute(
+            "INSERT INTO program_revision (program_id, help_text, version) VALUES (?1, ?2, ?3)",
+            params![program_id, help_text, detect_version(help_text)],
+        )?;
+        Ok(())
+    }
+
+    // Get the revisions of a program, oldest first
+    pub fn get_by_program(conn: &Connection, program_id: i32) -> Result<Vec<ProgramRevision>> {
+        let mut stmt = conn.prepare(
+            "SELECT help_text, version, created_at FROM program_revision
+             WHERE program_id = ?1 ORDER BY id",
+        )?;
+        let revision_iter = stmt.query_map([program_id], ProgramRevision::from_row)?;
+
+        let mut revisions = Vec::new();
+        for (index, revision) in revision_iter.enumerate() {
+            let mut revision = revision?;
+            revision.number = index + 1;
+            revisions.push(revision);
+        }
+
+        Ok(revisions)
+    }
+
+    fn from_row(row: &Row) -> Result<ProgramRevision> {
+        Ok(ProgramRevision {
+            number: 0,
+            help_text: row.get(0)?,
+            version: row.get(1)?,
+            created_at: row.get(2)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_connection;
+
+    #[test]
+    fn records_changed_help_texts_only() {
+        let conn = open_test_connection();
+        let mut program = Program::new("tool", Vec::new(), "tool 1.0\nUsage: tool\n");
+        program.id = Some(program.insert(&conn).unwrap());
+
+        program.update(&conn).unwrap();
+        program.help_text = "tool 1.1\nUsage: tool [-v]\n".to_string();
+        program.update(&conn).unwrap();
+
+        let revisions = ProgramRevision::get_by_program(&conn, program.id.unwrap()).unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].number, 1);
+        assert_eq!(revisions[0].version.as_deref(), Some("1.0"));
+        assert_eq!(revisions[1].number, 2);
+        assert_eq!(revisions[1].help_text, "tool 1.1\nUsage: tool [-v]\n");
+    }
+}
@@ -0,0 +1,69 @@
+use crate::db::program_tag::ProgramTag;
+use rusqlite::{Connection, Result};
+use serde::Serialize;
+
+// What the list command shows of a program, without loading its help text
+#[derive(Debug, Serialize)]
+pub struct ProgramSummary {
+    pub name: String,
+    pub commands: usize,
+    // Bytes of the help text
+    pub size: usize,
+    // UTC times of the first and the newest revision, as "YYYY-MM-DD HH:MM:SS"
+    pub added_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl ProgramSummary {
+    // Get the summaries of all programs, sorted by name
+    pub fn get_all(conn: &Connection) -> Result<Vec<ProgramSummary>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, LENGTH(CAST(help_text AS BLOB)),
+                (SELECT COUNT(*) FROM program_command WHERE program_id = program.id),
+                (SELECT MIN(created_at) FROM program_revision WHERE program_id = program.id),
+                (SELECT MAX(created_at) FROM program_revision WHERE program_id = program.id)
+             FROM program ORDER BY name COLLATE NOCASE",
+        )?;
+        let mut tags = ProgramTag::get_all(conn)?;
+        let summary_iter = stmt.query_map([], |row| {
+            Ok(ProgramSummary {
+                name: row.get(1)?,
+                commands: row.get(3)?,
+                size: row.get(2)?,
+                added_at: row.get(4)?,
+                updated_at: row.get(5)?,
+                tags: tags.remove(&row.get(0)?).unwrap_or_default(),
+            })
+        })?;
+        summary_iter.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::help_command::HelpCommand;
+    use crate::db::{open_test_connection, program::Program};
+
+    #[test]
+    fn summarizes_programs_by_name() {
+        let conn = open_test_connection();
+        let commands = HelpCommand::parse_list("tar --help; tar --usage", false).unwrap();
+        let tar = Program::new("tar", commands, "Usage: tar\n")
+            .insert(&conn)
+            .unwrap();
+        Program::new("Awk", Vec::new(), "").insert(&conn).unwrap();
+        ProgramTag::insert(&conn, tar, "archive").unwrap();
+        ProgramTag::insert(&conn, tar, "gnu").unwrap();
+
+        let summaries = ProgramSummary::get_all(&conn).unwrap();
+        let names: Vec<&str> = summaries.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Awk", "tar"]);
+        assert_eq!(summaries[1].commands, 2);
+        assert_eq!(summaries[1].size, 11);
+        assert_eq!(summaries[1].tags, ["archive", "gnu"]);
+        assert!(summaries[1].added_at.is_some());
+        assert!(summaries[0].tags.is_empty());
+    }
+}
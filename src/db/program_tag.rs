@@ -0,0 +1,60 @@
+use rusqlite::{params, Connection, Result};
+use std::collections::HashMap;
+
+// A label to group programs by, e.g. "vcs" for git and hg
+pub struct ProgramTag;
+
+impl ProgramTag {
+    // Create the tag table
+    pub fn create_table(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS program_tag (
+                program_id    INTEGER NOT NULL REFERENCES program (id) ON DELETE CASCADE,
+                tag           TEXT NOT NULL,
+                PRIMARY KEY (program_id, tag)
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    // Tag a program, returns false when it already had the tag
+    pub fn insert(conn: &Connection, program_id: i32, tag: &str) -> Result<bool> {
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO program_tag (program_id, tag) VALUES (?1, ?2)",
+            params![program_id, tag],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    // Remove a tag from a program, returns whether it had the tag
+    pub fn delete(conn: &Connection, program_id: i32, tag: &str) -> Result<bool> {
+        let deleted = conn.execute(
+            "DELETE FROM program_tag WHERE program_id = ?1 AND tag = ?2",
+            params![program_id, tag],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    // Get the tags of a program, sorted by name
+    pub fn get_by_program(conn: &Connection, program_id: i32) -> Result<Vec<String>> {
+        let mut stmt =
+            conn.prepare("SELECT tag FROM program_tag WHERE program_id = ?1 ORDER BY tag")?;
+        let tag_iter = stmt.query_map([program_id], |row| row.get(0))?;
+        tag_iter.collect()
+    }
+
+    // Get the tags of all programs by program ID, sorted by name
+    pub fn get_all(conn: &Connection) -> Result<HashMap<i32, Vec<String>>> {
+        let mut stmt = conn.prepare("SELECT program_id, tag FROM program_tag ORDER BY tag")?;
+        let tag_iter = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        for tag in tag_iter {
+            let (program_id, tag) = tag?;
+            tags.entry(program_id).or_default().push(tag);
+        }
+
+        Ok(tags)
+    }
+}
@@ -0,0 +1,60 @@
+use rusqlite::{params, Connection, Result};
+
+// The bytes a help command printed before they were cleaned up, kept for debugging
+#[derive(Debug, Clone)]
+pub struct RawOutput {
+    pub command: String,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+impl RawOutput {
+    // Create the raw_output table
+    pub fn create_table(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS raw_output (
+                id            INTEGER PRIMARY KEY AUTOINCREMENT,
+                program_id    INTEGER NOT NULL REFERENCES program (id) ON DELETE CASCADE,
+                command       TEXT NOT NULL,
+                stdout        BLOB NOT NULL,
+                stderr        BLOB NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    // Replace the raw output stored for a program
+    pub fn replace_for_program(
+        conn: &Connection,
+        program_id: i32,
+        outputs: &[RawOutput],
+    ) -> Result<()> {
+        RawOutput::delete_by_program(conn, program_id)?;
+        for output in outputs {
+            conn.execute(
+                "INSERT INTO raw_output (program_id, command, stdout, stderr) VALUES (?1, ?2, ?3, ?4)",
+                params![program_id, output.command, output.stdout, output.stderr],
+            )?;
+        }
+        Ok(())
+    }
+
+    // Check whether raw output was kept for a program
+    pub fn has_program(conn: &Connection, program_id: i32) -> Result<bool> {
+        conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM raw_output WHERE program_id = ?1)",
+            params![program_id],
+            |row| row.get(0),
+        )
+    }
+
+    // Delete the raw output stored for a program
+    pub fn delete_by_program(conn: &Connection, program_id: i32) -> Result<()> {
+        conn.execute(
+            "DELETE FROM raw_output WHERE program_id = ?1",
+            params![program_id],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use rusqlite::{params, Connection, Result};
+
+#[derive(Debug)]
+pub struct IndexHit {
+    pub program_id: i32,
+    pub score: f64,
+}
+
+// Create the full-text index over the program table and the triggers that keep it in sync
+pub fn create_index(conn: &Connection) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'program_fts')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS program_fts USING fts5(
+            name,
+            help_text,
+            content = 'program',
+            content_rowid = 'id'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS program_fts_insert AFTER INSERT ON program BEGIN
+            INSERT INTO program_fts (rowid, name, help_text)
+            VALUES (new.id, new.name, new.help_text);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS program_fts_delete AFTER DELETE ON program BEGIN
+            INSERT INTO program_fts (program_fts, rowid, name, help_text)
+            VALUES ('delete', old.id, old.name, old.help_text);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS program_fts_update AFTER UPDATE ON program BEGIN
+            INSERT INTO program_fts (program_fts, rowid, name, help_text)
+            VALUES ('delete', old.id, old.name, old.help_text);
+            INSERT INTO program_fts (rowid, name, help_text)
+            VALUES (new.id, new.name, new.help_text);
+        END;",
+    )?;
+
+    // Programs stored before the index existed have to be indexed once
+    if !exists {
+        rebuild_index(conn)?;
+    }
+
+    Ok(())
+}
+
+// Rebuild the full-text index from the contents of the program table
+pub fn rebuild_index(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT INTO program_fts (program_fts) VALUES ('rebuild')",
+        [],
+    )?;
+    Ok(())
+}
+
+// Find the programs matching any term of the query, best BM25 score first, so programs
+// matching more of the terms rank higher
+pub fn search_index(conn: &Connection, terms: &[&str]) -> Result<Vec<IndexHit>> {
+    let fts_query = terms
+        .iter()
+        .map(|t| format!("\"{}\"*", t.replace('"', "\"\"")))
+        .collect::<Vec<String>>()
+        .join(" OR ");
+
+    // Matches in the program name weigh more than matches in the help text
+    let mut stmt = conn.prepare(
+        "SELECT rowid, bm25(program_fts, 10.0, 1.0) AS score FROM program_fts
+         WHERE program_fts MATCH ?1
+         ORDER BY score",
+    )?;
+    let hit_iter = stmt.query_map(params![fts_query], |row| {
+        Ok(IndexHit {
+            program_id: row.get(0)?,
+            score: -row.get::<_, f64>(1)?,
+        })
+    })?;
+
+    let mut hits = Vec::new();
+    for hit in hit_iter {
+        hits.push(hit?);
+    }
+
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::help_command::HelpCommand;
+    use crate::db::{open_test_connection, program::Program};
+
+    #[test]
+    fn keeps_the_index_in_sync_with_the_program_table() {
+        let conn = open_test_connection();
+        Program::new(
+            "gzip",
+            vec![HelpCommand::parse("gzip --help", false).unwrap()],
+            "Compress files",
+        )
+        .insert(&conn)
+        .unwrap();
+
+        let hits = search_index(&conn, &["compress"]).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(search_index(&conn, &["comp"]).unwrap().len(), 1);
+
+        Program::delete(&conn, hits[0].program_id).unwrap();
+        assert!(search_index(&conn, &["compress"]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::db::program::Program;
+use crate::help_parser::sections::parse_sections;
+use rusqlite::{params, Connection, Result};
+
+// What a section of a help text is about, judged from its heading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SectionKind {
+    Name,
+    Synopsis,
+    Description,
+    Options,
+    Commands,
+    Examples,
+    Other,
+}
+
+impl SectionKind {
+    // Judge a heading like "SYNOPSIS", "Usage" or "Global Flags"
+    pub fn from_heading(heading: &str) -> Self {
+        let heading = heading.to_lowercase();
+
+        if heading.starts_with("usage") || heading.contains("synopsis") {
+            SectionKind::Synopsis
+        } else if heading == "name" {
+            SectionKind::Name
+        } else if heading.contains("example") {
+            SectionKind::Examples
+        } else if heading.contains("option")
+            || heading.contains("flag")
+            || heading.contains("argument")
+        {
+            SectionKind::Options
+        } else if heading.contains("command") {
+            SectionKind::Commands
+        } else if heading.contains("description") {
+            SectionKind::Description
+        } else {
+            SectionKind::Other
+        }
+    }
+
+    pub fn from_arg(s: &str) -> Self {
+        match s {
+            "name" => SectionKind::Name,
+            "synopsis" => SectionKind::Synopsis,
+            "description" => SectionKind::Description,
+            "options" => SectionKind::Options,
+            "commands" => SectionKind::Commands,
+            "examples" => SectionKind::Examples,
+            _ => SectionKind::Other,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SectionKind::Name => "name",
+            SectionKind::Synopsis => "synopsis",
+            SectionKind::Description => "description",
+            SectionKind::Options => "options",
+            SectionKind::Commands => "commands",
+            SectionKind::Examples => "examples",
+            SectionKind::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Section {
+    // The heading as written in the help text
+    pub name: String,
+    pub kind: SectionKind,
+    // Line numbers of the heading and of the last line before the next heading
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl Section {
+    // Create the section table, splitting the help texts of programs stored before it existed
+    pub fn create_table(conn: &Connection) -> Result<()> {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'section')",
+            [],
+            |row| row.get(0),
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS section (
+                id            INTEGER PRIMARY KEY AUTOINCREMENT,
+                program_id    INTEGER NOT NULL REFERENCES program (id) ON DELETE CASCADE,
+                name          TEXT NOT NULL,
+                kind          TEXT NOT NULL,
+                start_line    INTEGER NOT NULL,
+                end_line      INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        if !exists {
+            for (id, help_text) in Program::get_help_texts(conn)? {
+                Section::replace_for_program(conn, id, &help_text)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Insert the section for a program into the database
+    pub fn insert(&self, conn: &Connection, program_id: i32) -> Result<()> {
+        conn.execute(
+            "INSERT INTO section (program_id, name, kind, start_line, end_line)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                program_id,
+                self.name,
+                self.kind.as_str(),
+                self.start_line,
+                self.end_line
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Get the sections of a program in the order they appear in its help text
+    pub fn get_by_program(conn: &Connection, program_id: i32) -> Result<Vec<Section>> {
+        let mut stmt = conn.prepare(
+            "SELECT name, kind, start_line, end_line
+             FROM section WHERE program_id = ?1 ORDER BY start_line",
+        )?;
+        let section_iter = stmt.query_map([program_id], |row| {
+            Ok(Section {
+                name: row.get(0)?,
+                kind: SectionKind::from_arg(&row.get::<_, String>(1)?),
+                start_line: row.get(2)?,
+                end_line: row.get(3)?,
+            })
+        })?;
+
+        let mut sections = Vec::new();
+        for section in section_iter {
+            sections.push(section?);
+        }
+
+        Ok(sections)
+    }
+
+    // Replace the stored sections of a program with the ones found in its help text
+    pub fn replace_for_program(conn: &Connection, program_id: i32, help_text: &str) -> Result<()> {
+        Section::delete_by_program(conn, program_id)?;
+        for section in parse_sections(help_text) {
+            section.insert(conn, program_id)?;
+        }
+        Ok(())
+    }
+
+    // Delete all sections of a program
+    pub fn delete_by_program(conn: &Connection, program_id: i32) -> Result<()> {
+        conn.execute(
+            "DELETE FROM section WHERE program_id = ?1",
+            params![program_id],
+        )?;
+        Ok(())
+    }
+
+    // Find the section a line belongs to, text before the first heading has none
+    pub fn find(sections: &[Section], line_number: usize) -> Option<&Section> {
+        sections
+            .iter()
+            .find(|s| (s.start_line..=s.end_line).contains(&line_number))
+    }
+}
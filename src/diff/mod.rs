@@ -0,0 +1,183 @@
+pub mod unified;
+
+use crate::db::program_option::ProgramOption;
+use crate::help_parser::parse_options;
+
+// Texts that differ in more lines than this are compared as one replaced block, the table the
+// comparison needs grows with the product of both sides
+const MAX_COMPARED_CELLS: usize = 16_000_000;
+
+#[derive(Debug, PartialEq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+// Compare two texts line by line, keeping the longest run of common lines
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old = old.lines().collect::<Vec<&str>>();
+    let new = new.lines().collect::<Vec<&str>>();
+
+    // Upgrades usually change a few lines in the middle, so the common ends are not compared
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut diff = old[..prefix]
+        .iter()
+        .map(|l| DiffLine::Same(l))
+        .collect::<Vec<DiffLine>>();
+    diff.extend(diff_middle(old_middle, new_middle));
+    diff.extend(old[old.len() - suffix..].iter().map(|l| DiffLine::Same(l)));
+
+    diff
+}
+
+fn diff_middle<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    if (old.len() + 1) * (new.len() + 1) > MAX_COMPARED_CELLS {
+        let mut diff = old.iter().map(|l| DiffLine::Removed(l)).collect::<Vec<_>>();
+        diff.extend(new.iter().map(|l| DiffLine::Added(l)));
+        return diff;
+    }
+
+    // lengths[i][j] is the longest common subsequence of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|l| DiffLine::Removed(l)));
+    diff.extend(new[j..].iter().map(|l| DiffLine::Added(l)));
+
+    diff
+}
+
+// Count the added and removed lines of a diff
+pub fn count_changes(diff: &[DiffLine]) -> (usize, usize) {
+    diff.iter()
+        .fold((0, 0), |(added, removed), line| match line {
+            DiffLine::Added(_) => (added + 1, removed),
+            DiffLine::Removed(_) => (added, removed + 1),
+            DiffLine::Same(_) => (added, removed),
+        })
+}
+
+// The options that appeared or disappeared between two help texts
+#[derive(Debug, Default)]
+pub struct OptionChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+// Compare the options parsed from two help texts by their flags
+pub fn option_changes(old: &str, new: &str) -> OptionChanges {
+    let old_flags = option_keys(old);
+    let new_flags = option_keys(new);
+
+    OptionChanges {
+        added: new_flags
+            .iter()
+            .filter(|f| !old_flags.contains(f))
+            .cloned()
+            .collect(),
+        removed: old_flags
+            .iter()
+            .filter(|f| !new_flags.contains(f))
+            .cloned()
+            .collect(),
+    }
+}
+
+// Options are known by their long flag, the short one is used when there is no long flag
+fn option_keys(help_text: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for option in parse_options(help_text) {
+        if let Some(key) = option_key(&option) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    keys
+}
+
+fn option_key(option: &ProgramOption) -> Option<String> {
+    option
+        .long_flag
+        .clone()
+        .or_else(|| option.short_flag.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DiffLine::{Added, Removed, Same};
+
+    #[test]
+    fn keeps_common_lines() {
+        assert_eq!(
+            diff_lines("a\nb\nc\nd\n", "a\nc\nx\nd\n"),
+            [Same("a"), Removed("b"), Same("c"), Added("x"), Same("d")]
+        );
+    }
+
+    #[test]
+    fn compares_texts_without_common_lines() {
+        assert_eq!(
+            diff_lines("a\nb", "c"),
+            [Removed("a"), Removed("b"), Added("c")]
+        );
+        assert_eq!(diff_lines("", "a"), [Added("a")]);
+        assert_eq!(diff_lines("a\nb", "a\nb"), [Same("a"), Same("b")]);
+    }
+
+    #[test]
+    fn finds_the_longest_common_subsequence() {
+        let diff = diff_lines("x\na\nb\nc\ny", "x\nb\nc\na\ny");
+
+        // Keeping "b" and "c" moves one line instead of two
+        assert_eq!(count_changes(&diff), (1, 1));
+        assert_eq!(diff.iter().filter(|l| matches!(l, Same(_))).count(), 4);
+    }
+
+    #[test]
+    fn compares_options_by_flag() {
+        let changes = option_changes(
+            "  -a, --all    show all\n  -q           quiet\n",
+            "  -a, --all    show everything\n  -v, --verbose  talk more\n",
+        );
+
+        assert_eq!(changes.added, ["--verbose"]);
+        assert_eq!(changes.removed, ["-q"]);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::diff::DiffLine;
+use colored::Colorize;
+
+// A run of changes with the unchanged lines around it, like a hunk of `diff -u`
+#[derive(Debug)]
+pub struct Hunk<'a> {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<&'a DiffLine<'a>>,
+}
+
+// Group a diff into hunks with `context` unchanged lines around each change
+pub fn hunks<'a>(diff: &'a [DiffLine<'a>], context: usize) -> Vec<Hunk<'a>> {
+    let changes = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect::<Vec<usize>>();
+
+    // Changes closer than twice the context share a hunk
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for index in changes {
+        let start = index.saturating_sub(context);
+        let end = (index + context).min(diff.len() - 1);
+        match ranges.last_mut() {
+            Some((_, last_end)) if start <= *last_end + 1 => *last_end = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    // Line numbers in the old and new text at the start of every diff line
+    let mut old_line = 1;
+    let mut new_line = 1;
+    let mut positions = Vec::with_capacity(diff.len());
+    for line in diff {
+        positions.push((old_line, new_line));
+        match line {
+            DiffLine::Same(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            DiffLine::Removed(_) => old_line += 1,
+            DiffLine::Added(_) => new_line += 1,
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let lines = diff[start..=end].iter().collect::<Vec<&DiffLine>>();
+            let old_len = lines
+                .iter()
+                .filter(|l| !matches!(l, DiffLine::Added(_)))
+                .count();
+            let new_len = lines
+                .iter()
+                .filter(|l| !matches!(l, DiffLine::Removed(_)))
+                .count();
+
+            Hunk {
+                old_start: positions[start].0,
+                old_len,
+                new_start: positions[start].1,
+                new_len,
+                lines,
+            }
+        })
+        .collect()
+}
+
+// Print a diff in the unified format
+pub fn print_unified(old_label: &str, new_label: &str, diff: &[DiffLine], context: usize) {
+    println!("{}", format!("--- {}", old_label).bold());
+    println!("{}", format!("+++ {}", new_label).bold());
+
+    for hunk in hunks(diff, context) {
+        println!(
+            "{}",
+            format!(
+                "@@ -{} +{} @@",
+                hunk_range(hunk.old_start, hunk.old_len),
+                hunk_range(hunk.new_start, hunk.new_len)
+            )
+            .cyan()
+        );
+
+        for line in hunk.lines {
+            match line {
+                DiffLine::Same(text) => println!(" {}", text),
+                DiffLine::Removed(text) => println!("{}", format!("-{}", text).red()),
+                DiffLine::Added(text) => println!("{}", format!("+{}", text).green()),
+            }
+        }
+    }
+}
+
+// Ranges are written as "start,length", an empty range starts at the line before it
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start - 1),
+        1 => start.to_string(),
+        _ => format!("{},{}", start, len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::diff_lines;
+
+    #[test]
+    fn groups_nearby_changes_into_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\neleven\n";
+        let diff = diff_lines(old, new);
+
+        let split = hunks(&diff, 1);
+        assert_eq!(split.len(), 2);
+        assert_eq!(
+            (
+                split[0].old_start,
+                split[0].old_len,
+                split[0].new_start,
+                split[0].new_len
+            ),
+            (2, 3, 2, 3)
+        );
+        assert_eq!(
+            (
+                split[1].old_start,
+                split[1].old_len,
+                split[1].new_start,
+                split[1].new_len
+            ),
+            (10, 1, 10, 2)
+        );
+
+        assert_eq!(hunks(&diff, 4).len(), 1);
+    }
+
+    #[test]
+    fn writes_ranges_like_diff() {
+        assert_eq!(hunk_range(3, 2), "3,2");
+        assert_eq!(hunk_range(3, 1), "3");
+        assert_eq!(hunk_range(3, 0), "2,0");
+    }
+}
@@ -0,0 +1,205 @@
+use crate::command::errors::GetCommandStringError;
+use crate::db::migration::MigrationError;
+use crate::search::errors::SearchError;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Exit codes, 1 is used like grep does for searches without matches
+pub const EXIT_NO_MATCHES: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_DATABASE: i32 = 3;
+pub const EXIT_IO: i32 = 4;
+pub const EXIT_COMMAND: i32 = 5;
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug)]
+pub enum SearchHelpError {
+    DatabaseError(rusqlite::Error),
+    MigrationError(MigrationError),
+    IoError(std::io::Error),
+    CommandError(GetCommandStringError),
+    SearchError(SearchError),
+    ProgramNotFound(String),
+    InvalidInput(String),
+    NoMatches(String),
+    // What was being done when an error happened, the error is its source
+    Context(String, Box<SearchHelpError>),
+}
+
+impl SearchHelpError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            SearchHelpError::DatabaseError(_) | SearchHelpError::MigrationError(_) => EXIT_DATABASE,
+            SearchHelpError::IoError(_) => EXIT_IO,
+            SearchHelpError::CommandError(_) => EXIT_COMMAND,
+            SearchHelpError::SearchError(SearchError::DatabaseError(_)) => EXIT_DATABASE,
+            SearchHelpError::SearchError(_)
+            | SearchHelpError::ProgramNotFound(_)
+            | SearchHelpError::InvalidInput(_) => EXIT_USAGE,
+            SearchHelpError::NoMatches(_) => EXIT_NO_MATCHES,
+            SearchHelpError::Context(_, error) => error.exit_code(),
+        }
+    }
+
+    // The error below all contexts
+    fn root(&self) -> &SearchHelpError {
+        match self {
+            SearchHelpError::Context(_, error) => error.root(),
+            error => error,
+        }
+    }
+}
+
+// Implement the Display trait for the SearchHelpError enum
+impl fmt::Display for SearchHelpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchHelpError::DatabaseError(err) => write!(f, "{}", err),
+            SearchHelpError::MigrationError(err) => write!(f, "{}", err),
+            SearchHelpError::IoError(err) => write!(f, "{}", err),
+            SearchHelpError::CommandError(err) => write!(f, "{}", err),
+            SearchHelpError::SearchError(err) => write!(f, "{}", err),
+            SearchHelpError::ProgramNotFound(name) => {
+                write!(f, "Program \"{}\" does not exist", name)
+            }
+            SearchHelpError::InvalidInput(message) => write!(f, "{}", message),
+            SearchHelpError::NoMatches(message) => write!(f, "{}", message),
+            SearchHelpError::Context(context, _) => write!(f, "{}", context),
+        }
+    }
+}
+
+impl Error for SearchHelpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SearchHelpError::DatabaseError(err) => err.source(),
+            SearchHelpError::MigrationError(err) => err.source(),
+            SearchHelpError::IoError(err) => err.source(),
+            SearchHelpError::CommandError(err) => err.source(),
+            SearchHelpError::SearchError(err) => err.source(),
+            SearchHelpError::Context(_, err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for SearchHelpError {
+    fn from(err: rusqlite::Error) -> SearchHelpError {
+        SearchHelpError::DatabaseError(err)
+    }
+}
+
+impl From<MigrationError> for SearchHelpError {
+    fn from(err: MigrationError) -> SearchHelpError {
+        SearchHelpError::MigrationError(err)
+    }
+}
+
+impl From<std::io::Error> for SearchHelpError {
+    fn from(err: std::io::Error) -> SearchHelpError {
+        SearchHelpError::IoError(err)
+    }
+}
+
+impl From<GetCommandStringError> for SearchHelpError {
+    fn from(err: GetCommandStringError) -> SearchHelpError {
+        SearchHelpError::CommandError(err)
+    }
+}
+
+impl From<SearchError> for SearchHelpError {
+    fn from(err: SearchError) -> SearchHelpError {
+        SearchHelpError::SearchError(err)
+    }
+}
+
+// Add what was being done to the error of a result
+pub trait Context<T> {
+    fn context(self, context: impl Into<String>) -> Result<T, SearchHelpError>;
+}
+
+impl<T, E: Into<SearchHelpError>> Context<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, SearchHelpError> {
+        self.map_err(|err| SearchHelpError::Context(context.into(), Box::new(err.into())))
+    }
+}
+
+// Show the whole chain of causes when reporting errors
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+// Print an error to stderr, the context and the error below it, or with --verbose every cause
+// Errors that wrap another one, like a database error, already show it in their Display, so
+// their source() skips to the source of the wrapped error and no cause is printed twice
+pub fn report(error: &SearchHelpError) {
+    if let SearchHelpError::NoMatches(message) = error {
+        eprintln!("{}", message);
+        return;
+    }
+
+    if VERBOSE.load(Ordering::Relaxed) {
+        eprintln!("Error: {}", error);
+        let mut source = error.source();
+        while let Some(cause) = source {
+            eprintln!("  caused by: {}", cause);
+            source = cause.source();
+        }
+        return;
+    }
+
+    let root = error.root();
+    if std::ptr::eq(root, error) {
+        eprintln!("Error: {}", error);
+    } else {
+        eprintln!("Error: {}: {}", error, root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn not_found() -> Result<(), SearchHelpError> {
+        Err(SearchHelpError::ProgramNotFound("tool".to_string()))
+    }
+
+    #[test]
+    fn maps_errors_to_exit_codes() {
+        assert_eq!(
+            SearchHelpError::from(MigrationError::NewerDatabase(99)).exit_code(),
+            EXIT_DATABASE
+        );
+        assert_eq!(
+            SearchHelpError::from(std::io::Error::other("disk full")).exit_code(),
+            EXIT_IO
+        );
+        assert_eq!(
+            SearchHelpError::NoMatches(String::new()).exit_code(),
+            EXIT_NO_MATCHES
+        );
+        assert_eq!(not_found().unwrap_err().exit_code(), EXIT_USAGE);
+    }
+
+    #[test]
+    fn contexts_keep_the_exit_code_and_chain_the_error() {
+        let error = not_found()
+            .context("Error refreshing tool")
+            .context("Error refreshing all programs")
+            .unwrap_err();
+
+        assert_eq!(error.exit_code(), EXIT_USAGE);
+        assert_eq!(error.to_string(), "Error refreshing all programs");
+        assert_eq!(error.root().to_string(), "Program \"tool\" does not exist");
+
+        let causes = std::iter::successors(error.source(), |e| (*e).source())
+            .map(|e| e.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            causes,
+            ["Error refreshing tool", "Program \"tool\" does not exist"]
+        );
+    }
+}
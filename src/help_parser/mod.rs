@@ -0,0 +1,236 @@
+pub mod sections;
+pub mod version;
+
+use crate::db::program_option::ProgramOption;
+use regex::Regex;
+
+// Pull the options out of help text in the common GNU, clap, argparse, cobra and man layouts
+pub fn parse_options(help_text: &str) -> Vec<ProgramOption> {
+    let lines = help_text.lines().collect::<Vec<&str>>();
+    let default_regex = Regex::new(
+        r#"(?i)[\[(]\s*defaults?(?:\s+(?:is|to|value))?\s*[:=]?\s*"?([^\])"]*)"?\s*[\])]"#,
+    )
+    .expect("Default value pattern is valid");
+
+    let mut options = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let Some((flags, description)) = split_option_line(line) else {
+            continue;
+        };
+        let Some(mut option) = parse_flags(flags) else {
+            continue;
+        };
+
+        // Wrapped description lines are indented deeper than the flag line, the next flag
+        // line or a blank line ends the description
+        let mut description = description.to_string();
+        for next in lines.iter().skip(index + 1) {
+            if next.trim().is_empty() || indent(next) <= indent(line) || is_option_line(next) {
+                break;
+            }
+            if !description.is_empty() {
+                description.push(' ');
+            }
+            description.push_str(next.trim());
+        }
+
+        option.default_value = default_regex
+            .captures(&description)
+            .map(|c| c[1].trim().to_string())
+            .filter(|d| !d.is_empty());
+        option.description = description;
+        option.line_number = index + 1;
+
+        options.push(option);
+    }
+
+    options
+}
+
+// Split a line into its flags and the description following them
+fn split_option_line(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim();
+    if !looks_like_flag(trimmed) {
+        return None;
+    }
+
+    // Descriptions are separated from the flags by a wide gap, or by a colon as nmap does
+    let gap = [trimmed.find("  "), trimmed.find('\t'), trimmed.find(": ")]
+        .into_iter()
+        .flatten()
+        .min();
+
+    match gap {
+        Some(gap) => {
+            let description = trimmed[gap..].trim_start_matches(':').trim();
+            Some((&trimmed[..gap], description))
+        }
+        None => Some((trimmed, "")),
+    }
+}
+
+fn is_option_line(line: &str) -> bool {
+    split_option_line(line).is_some_and(|(flags, _)| parse_flags(flags).is_some())
+}
+
+// Parse flags like "-o, --output <FILE>", "--color[=WHEN]" or "-w, --wordlist string"
+fn parse_flags(flags: &str) -> Option<ProgramOption> {
+    let mut option = ProgramOption::default();
+    let tokens = tokenize(flags);
+
+    for token in &tokens {
+        if token.starts_with('-') {
+            // Alternatives like "-oN/-oX" or "--min-rtt-timeout/max-rtt-timeout" keep the first
+            let (flag, value) = match token.find(['=', '[']) {
+                Some(split) => (&token[..split], Some(&token[split..])),
+                None => (token.as_str(), None),
+            };
+            let flag = flag.split('/').next().unwrap_or(flag);
+
+            match flag.strip_prefix("--") {
+                Some(name) if is_flag_name(name) => {
+                    option.long_flag.get_or_insert(flag.to_string());
+                }
+                None if is_flag_name(&flag[1..]) => {
+                    option.short_flag.get_or_insert(flag.to_string());
+                }
+                _ => return None,
+            }
+
+            if let Some(value) = value {
+                option.value.get_or_insert(value.replace('=', ""));
+            }
+        } else if token.starts_with('(') {
+            // Man pages put a short title in parentheses after the flags
+            break;
+        } else if option.value.is_none() {
+            option.value = Some(token.to_string());
+        } else if option.value.as_ref() != Some(token) {
+            // More words than a placeholder means this is a sentence starting with a flag
+            return None;
+        }
+    }
+
+    if option.short_flag.is_none() && option.long_flag.is_none() {
+        return None;
+    }
+
+    Some(option)
+}
+
+// Split flags on separators, keeping placeholders like "<host1[,host2]>" in one piece
+fn tokenize(flags: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut depth = 0;
+
+    for c in flags.chars() {
+        match c {
+            '<' | '[' | '{' => depth += 1,
+            '>' | ']' | '}' => depth = (depth - 1).max(0),
+            _ => {}
+        }
+
+        if depth == 0 && (c.is_whitespace() || c == ',' || c == ';') {
+            if !token.is_empty() && token != "|" {
+                tokens.push(std::mem::take(&mut token));
+            }
+            token.clear();
+        } else {
+            token.push(c);
+        }
+    }
+
+    if !token.is_empty() && token != "|" {
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+fn looks_like_flag(s: &str) -> bool {
+    let name = s.strip_prefix("--").or_else(|| s.strip_prefix('-'));
+    name.and_then(|n| n.chars().next())
+        .is_some_and(|c| c.is_alphanumeric() || c == '?')
+}
+
+fn is_flag_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || "-_.?#@".contains(c))
+}
+
+fn indent(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gnu_options() {
+        let options = parse_options(
+            "Usage: ls [OPTION]...\n\
+             \n\
+             \x20 -a, --all                  do not ignore entries starting with .\n\
+             \x20     --color[=WHEN]         color the output WHEN\n\
+             \x20 -w, --width=COLS           set output width to COLS\n",
+        );
+
+        assert_eq!(options.len(), 3);
+        assert_eq!(options[0].short_flag.as_deref(), Some("-a"));
+        assert_eq!(options[0].long_flag.as_deref(), Some("--all"));
+        assert_eq!(
+            options[0].description,
+            "do not ignore entries starting with ."
+        );
+        assert_eq!(options[0].line_number, 3);
+        assert_eq!(options[1].long_flag.as_deref(), Some("--color"));
+        assert_eq!(options[1].value.as_deref(), Some("[WHEN]"));
+        assert_eq!(options[2].flags(), "-w, --width COLS");
+    }
+
+    #[test]
+    fn joins_wrapped_descriptions_and_finds_defaults() {
+        let options = parse_options(
+            "Options:\n\
+             \x20 -t, --threads <N>\n\
+             \x20         Number of threads to use\n\
+             \x20         [default: 4]\n\
+             \x20 -h, --help  Print help\n",
+        );
+
+        assert_eq!(options.len(), 2);
+        assert_eq!(options[0].value.as_deref(), Some("<N>"));
+        assert_eq!(
+            options[0].description,
+            "Number of threads to use [default: 4]"
+        );
+        assert_eq!(options[0].default_value.as_deref(), Some("4"));
+        assert_eq!(options[1].description, "Print help");
+    }
+
+    #[test]
+    fn parses_nmap_style_options() {
+        let options = parse_options("  -oN/-oX <file>: Output scan in normal and XML format\n");
+
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].short_flag.as_deref(), Some("-oN"));
+        assert_eq!(options[0].value.as_deref(), Some("<file>"));
+        assert_eq!(
+            options[0].description,
+            "Output scan in normal and XML format"
+        );
+    }
+
+    #[test]
+    fn skips_sentences_starting_with_a_flag() {
+        let options = parse_options("  --all is implied when no files are given\n  -1\n");
+
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].short_flag.as_deref(), Some("-1"));
+    }
+}
@@ -0,0 +1,235 @@
+use crate::db::section::{Section, SectionKind};
+use crate::help_parser::parse_options;
+
+// Longer lines are sentences, not headings
+const MAX_HEADING_WORDS: usize = 6;
+
+// Split a help text into the sections under its headings, like "SYNOPSIS" in man pages or
+// "Options:" in --help output
+pub fn parse_sections(help_text: &str) -> Vec<Section> {
+    let lines = help_text.lines().collect::<Vec<&str>>();
+    let mut sections: Vec<Section> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let next = lines.iter().skip(index + 1).find(|l| !l.trim().is_empty());
+        let Some(name) = heading(line, next.copied()) else {
+            continue;
+        };
+
+        if let Some(previous) = sections.last_mut() {
+            previous.end_line = index;
+        }
+        sections.push(Section {
+            kind: SectionKind::from_heading(&name),
+            name,
+            start_line: index + 1,
+            end_line: lines.len(),
+        });
+    }
+
+    split_usage(&lines, &mut sections);
+
+    // Headings like "HOST DISCOVERY" say nothing about their kind, but hold options
+    let option_lines = parse_options(help_text)
+        .into_iter()
+        .map(|o| o.line_number)
+        .collect::<Vec<usize>>();
+    for section in sections.iter_mut() {
+        if section.kind == SectionKind::Other
+            && option_lines
+                .iter()
+                .any(|l| (section.start_line..=section.end_line).contains(l))
+        {
+            section.kind = SectionKind::Options;
+        }
+    }
+
+    // Name the sections split off after a usage by what they hold
+    for section in sections.iter_mut().filter(|s| s.name.is_empty()) {
+        if section.kind == SectionKind::Options {
+            section.name = "Options".to_string();
+        } else {
+            section.kind = SectionKind::Description;
+            section.name = "Description".to_string();
+        }
+    }
+
+    sections
+}
+
+// A "Usage:" line only starts a paragraph, in --help output like that of ls the description and
+// the options follow it without a heading of their own, so they get an unnamed section
+fn split_usage(lines: &[&str], sections: &mut Vec<Section>) {
+    let mut index = 0;
+    while index < sections.len() {
+        let section = &sections[index];
+        index += 1;
+        if !lines[section.start_line - 1]
+            .to_lowercase()
+            .starts_with("usage:")
+        {
+            continue;
+        }
+
+        // Indexes of the blank line ending the usage and of the first line after it
+        let Some(blank) =
+            (section.start_line..section.end_line).find(|&l| lines[l].trim().is_empty())
+        else {
+            continue;
+        };
+        let Some(rest) = (blank..section.end_line).find(|&l| !lines[l].trim().is_empty()) else {
+            continue;
+        };
+
+        let end_line = section.end_line;
+        sections[index - 1].end_line = blank;
+        sections.insert(
+            index,
+            Section {
+                name: String::new(),
+                kind: SectionKind::Other,
+                start_line: rest + 1,
+                end_line,
+            },
+        );
+    }
+}
+
+// Get the name of the section a line starts, headings are not indented and either end in a
+// colon, are written in capitals, or are followed by indented text
+fn heading(line: &str, next: Option<&str>) -> Option<String> {
+    let trimmed = line.trim_end();
+    if trimmed.is_empty() || line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    // Flags and the notes about where a help text came from
+    if trimmed.starts_with('-') || trimmed.starts_with('[') {
+        return None;
+    }
+
+    // "Usage: tool [OPTIONS]" starts the synopsis on the heading line itself
+    if trimmed.to_lowercase().starts_with("usage:") {
+        return Some(trimmed[..5].to_string());
+    }
+
+    let name = trimmed.strip_suffix(':').unwrap_or(trimmed).trim();
+    if name.is_empty() || name.split_whitespace().count() > MAX_HEADING_WORDS {
+        return None;
+    }
+
+    let capitals = name.chars().filter(|c| c.is_alphabetic()).count() >= 2
+        && !name.chars().any(|c| c.is_lowercase());
+    let followed_by_indent = next.is_some_and(|n| n.starts_with(char::is_whitespace))
+        && name.starts_with(char::is_uppercase)
+        && !name.ends_with(['.', ',', ';']);
+
+    if trimmed.ends_with(':') || capitals || followed_by_indent {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Name, kind and line range of every section
+    fn outline(help_text: &str) -> Vec<(String, SectionKind, usize, usize)> {
+        parse_sections(help_text)
+            .into_iter()
+            .map(|s| (s.name, s.kind, s.start_line, s.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn splits_man_page_sections() {
+        let sections = outline(
+            "NAME\n\
+             \x20      tar - an archiving utility\n\
+             SYNOPSIS\n\
+             \x20      tar [OPTION...] [FILE]...\n\
+             DESCRIPTION\n\
+             \x20      Archive files.\n\
+             EXAMPLES\n\
+             \x20      tar -cf archive.tar foo bar\n",
+        );
+
+        assert_eq!(
+            sections,
+            [
+                ("NAME".to_string(), SectionKind::Name, 1, 2),
+                ("SYNOPSIS".to_string(), SectionKind::Synopsis, 3, 4),
+                ("DESCRIPTION".to_string(), SectionKind::Description, 5, 6),
+                ("EXAMPLES".to_string(), SectionKind::Examples, 7, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn ends_usage_at_its_first_blank_line() {
+        let sections = outline(
+            "Usage: ls [OPTION]... [FILE]...\n\
+             List information about the FILEs.\n\
+             \n\
+             Mandatory arguments to long options are mandatory for short options too.\n\
+             \x20 -a, --all                  do not ignore entries starting with .\n\
+             \x20 -l                         use a long listing format\n\
+             \n\
+             Exit status:\n\
+             \x200  if OK,\n",
+        );
+
+        assert_eq!(
+            sections,
+            [
+                ("Usage".to_string(), SectionKind::Synopsis, 1, 2),
+                ("Options".to_string(), SectionKind::Options, 4, 7),
+                ("Exit status".to_string(), SectionKind::Other, 8, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_after_usage_without_options_is_the_description() {
+        let sections = outline("usage: tool <file>\n\nCompress files quickly.\n");
+
+        assert_eq!(
+            sections,
+            [
+                ("usage".to_string(), SectionKind::Synopsis, 1, 1),
+                ("Description".to_string(), SectionKind::Description, 3, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn sections_holding_options_are_options() {
+        let sections = outline(
+            "HOST DISCOVERY:\n\
+             \x20 -sL: List Scan - simply list targets to scan\n",
+        );
+
+        assert_eq!(sections[0].1, SectionKind::Options);
+    }
+
+    #[test]
+    fn recognizes_headings() {
+        assert_eq!(heading("Options:", None).as_deref(), Some("Options"));
+        assert_eq!(
+            heading("GLOBAL FLAGS", None).as_deref(),
+            Some("GLOBAL FLAGS")
+        );
+        assert_eq!(
+            heading("Examples", Some("  tool -v")).as_deref(),
+            Some("Examples")
+        );
+        assert_eq!(heading("  Indented:", None), None);
+        assert_eq!(heading("--flag:", None), None);
+        assert_eq!(
+            heading("This sentence is a lot longer than a heading:", None),
+            None
+        );
+        assert_eq!(heading("Plain text line", None), None);
+    }
+}
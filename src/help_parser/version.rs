@@ -0,0 +1,79 @@
+use regex::Regex;
+
+// Version banners sit at the top of --help output and at the bottom of man pages
+const BANNER_LINES: usize = 5;
+
+// Find the version of the tool a help text describes, e.g. "9.1" in the footer
+// "GNU coreutils 9.1" or "2.43.0" in "git version 2.43.0"
+pub fn detect_version(help_text: &str) -> Option<String> {
+    let labeled = Regex::new(r"(?i)\bversion:?\s+v?(\d+(?:\.\d+)+[\w+-]*)")
+        .expect("Version pattern is valid");
+    // A few words and a version of up to three numbers, so addresses like 192.168.0.0 and
+    // command lines with flags are left alone
+    let banner = Regex::new(
+        r"^\s*[A-Za-z][\w()]*(?:\s+[A-Za-z][\w()]*){0,3}\s+v?(\d+\.\d+(?:\.\d+)?[\w+-]*)(?:[\s,)]|$)",
+    )
+        .expect("Banner pattern is valid");
+
+    let lines = help_text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<&str>>();
+    let banner_lines = lines
+        .iter()
+        .take(BANNER_LINES)
+        .chain(lines.iter().rev().take(BANNER_LINES))
+        .collect::<Vec<&&str>>();
+
+    // Versions mentioned elsewhere are usually about licenses or other tools
+    banner_lines
+        .iter()
+        .find_map(|line| labeled.captures(line))
+        .or_else(|| banner_lines.iter().find_map(|line| banner.captures(line)))
+        .map(|captures| captures[1].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_labeled_versions() {
+        assert_eq!(
+            detect_version("git version 2.43.0\nusage: git").as_deref(),
+            Some("2.43.0")
+        );
+        assert_eq!(
+            detect_version("Tool\nVersion: v1.2.3-beta\n").as_deref(),
+            Some("1.2.3-beta")
+        );
+    }
+
+    #[test]
+    fn finds_banner_versions_at_either_end() {
+        assert_eq!(
+            detect_version("curl 8.5.0 (x86_64-pc-linux-gnu)\nUsage: curl").as_deref(),
+            Some("8.5.0")
+        );
+
+        let mut man_page = "NAME\n    ls - list directory contents\n".repeat(5);
+        man_page.push_str("GNU coreutils 9.1        September 2022\n");
+        assert_eq!(detect_version(&man_page).as_deref(), Some("9.1"));
+    }
+
+    #[test]
+    fn ignores_versions_outside_the_banner() {
+        let mut help_text = "Usage: tool [OPTIONS]\n".to_string();
+        help_text.push_str(&"  -x  do something\n".repeat(10));
+        help_text.push_str("  --gpl  Licensed under GPL version 3.0\n");
+        help_text.push_str(&"  -y  do something else\n".repeat(10));
+
+        assert_eq!(detect_version(&help_text), None);
+    }
+
+    #[test]
+    fn ignores_addresses_and_command_lines() {
+        assert_eq!(detect_version("Scan 192.168.0.0/24 for hosts"), None);
+        assert_eq!(detect_version("Example: tool -p 8080 1.5"), None);
+    }
+}
@@ -1,19 +1,13 @@
 use colored::ColoredString;
 
 pub enum ListIndicator {
-    NoIndent,
-    Indent,
     Bullet,
-    Arrow,
 }
 
 impl ListIndicator {
     pub fn as_str(&self) -> &'static str {
         match self {
-            ListIndicator::NoIndent => "",
-            ListIndicator::Indent => "  ",
             ListIndicator::Bullet => "•",
-            ListIndicator::Arrow => "→",
         }
     }
 }
@@ -12,11 +12,8 @@ use std::io::{self, Write};
 pub fn print_list<T>(items: Vec<ListItem<T>>, settings: ListSettings) -> std::io::Result<()> {
     let indicator = settings.list_indicator.as_str();
 
-    terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
-
-    execute!(stdout, terminal::EnterAlternateScreen)?;
-    execute!(stdout, cursor::Hide)?;
+    enter_full_screen(&mut stdout)?;
 
     let mut selected = 0;
 
@@ -35,42 +32,28 @@ pub fn print_list<T>(items: Vec<ListItem<T>>, settings: ListSettings) -> std::io
         if let event::Event::Key(key_event) = event::read()? {
             match key_event.code {
                 KeyCode::Up => {
-                    if selected > 0 {
-                        selected -= 1;
-                    }
+                    selected = selected.saturating_sub(1);
                 }
-                KeyCode::Down => {
-                    if selected < items.len() - 1 {
-                        selected += 1;
-                    }
+                KeyCode::Down if selected < items.len() - 1 => {
+                    selected += 1;
                 }
                 KeyCode::Char('j') => {
-                    if selected > 0 {
-                        selected -= 1;
-                    }
-                }
-                KeyCode::Char('k') => {
-                    if selected < items.len() - 1 {
-                        selected += 1;
-                    }
+                    selected = selected.saturating_sub(1);
                 }
-                KeyCode::Enter => {
-                    if selected < items.len() {
-                        let item = &items[selected];
-                        if item.callback() {
-                            break;
-                        }
-                    }
+                KeyCode::Char('k') if selected < items.len() - 1 => {
+                    selected += 1;
                 }
-                KeyCode::Char('x') => {
-                    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+                KeyCode::Enter if selected < items.len() => {
+                    let item = &items[selected];
+                    if item.callback() {
                         break;
                     }
                 }
-                KeyCode::Char('c') => {
-                    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-                        break;
-                    }
+                KeyCode::Char('x') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break;
+                }
+                KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break;
                 }
                 KeyCode::Esc => {
                     break;
@@ -86,10 +69,21 @@ pub fn print_list<T>(items: Vec<ListItem<T>>, settings: ListSettings) -> std::io
         stdout.flush()?;
     }
 
-    // Restore terminal settings
+    leave_full_screen(&mut stdout)
+}
+
+// Switch to the alternate screen in raw mode, the shell's screen is restored when leaving it
+pub fn enter_full_screen(stdout: &mut io::Stdout) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen)?;
+    execute!(stdout, cursor::Hide)?;
+    Ok(())
+}
+
+// Restore terminal settings
+pub fn leave_full_screen(stdout: &mut io::Stdout) -> io::Result<()> {
     terminal::disable_raw_mode()?;
     execute!(stdout, cursor::Show)?;
     execute!(stdout, terminal::LeaveAlternateScreen)?;
-
     Ok(())
 }
@@ -2,45 +2,192 @@ mod choise;
 mod cli;
 mod command;
 mod db;
+mod diff;
+mod errors;
+mod help_parser;
 mod list;
+mod man;
+mod pager;
+mod search;
 
 use choise::print_choice;
 use clap::ArgMatches;
 use colored::Colorize;
-use command::get_command_texts;
+use command::{
+    binary::{binary_name, binary_unchanged, inspect_binary, probe_version, resolve_binary},
+    get_command_texts,
+    help_command::HelpCommand,
+    subcommands::{find_subcommands, get_subcommand_texts},
+    CommandText,
+};
 use core::str;
 use crossterm::{cursor, event, execute, terminal};
-use db::{open_connection, program::Program};
+use db::{
+    alias::Alias, default_database_path, migration::migrate, move_database, open_connection,
+    program::Program, program_binary::ProgramBinary, program_option::ProgramOption,
+    program_revision::ProgramRevision, program_summary::ProgramSummary, program_tag::ProgramTag,
+    raw_output::RawOutput, section::SectionKind, set_database_path, DATABASE_ENV,
+    LEGACY_DATABASE_FILE,
+};
+use diff::{count_changes, diff_lines, option_changes, unified::print_unified};
+use errors::{report, set_verbose, Context, SearchHelpError};
+use glob::{MatchOptions, Pattern};
 use list::list_settings::ListIndicator;
 use list::{list_item::ListItem, list_settings::ListSettings, print_list};
-use std::io::{self, Write};
+use pager::show_text;
+use rusqlite::Connection;
+use search::{
+    context::ContextMode,
+    matcher::Matcher,
+    output::{print_json_results, print_option_results, print_results, ColorChoice, OutputFormat},
+    program_filter::resolve_programs,
+    search_options::SearchOptions,
+    search_program_options, search_programs,
+};
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
 
 fn main() {
+    // Stop quietly when the output is piped into a program like head that exits early
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+
     let mut cli = cli::cli();
-    let matches = cli.clone().get_matches();
+    let matches = cli
+        .clone()
+        .get_matches_from(cli::normalize_search_args(env::args_os()));
+    set_verbose(matches.get_flag("verbose"));
+
+    if let Err(e) = run(&mut cli, &matches) {
+        report(&e);
+        std::process::exit(e.exit_code());
+    }
+}
 
-    let conn = open_connection().expect("Error opening database");
-    Program::create_table(&conn).expect("Error creating Program table");
+fn run(cli: &mut clap::Command, matches: &ArgMatches) -> Result<(), SearchHelpError> {
+    set_database_path(database_path(matches)?);
+    let mut conn = open_connection().context("Could not open the database")?;
+    migrate(&mut conn).context("Could not open the database")?;
 
     match matches.subcommand() {
         Some(("add", sub_m)) => add(sub_m),
         Some(("update", sub_m)) => update(sub_m),
         Some(("delete", sub_m)) => delete(sub_m),
+        Some(("refresh", sub_m)) => refresh(sub_m),
+        Some(("alias", sub_m)) => alias(sub_m),
+        Some(("tag", sub_m)) => tag(sub_m),
+        Some(("list", sub_m)) => list_programs(sub_m),
+        Some(("show", sub_m)) => show(sub_m),
+        Some(("info", sub_m)) => info(sub_m),
+        Some(("history", sub_m)) => history(sub_m),
+        Some(("diff", sub_m)) => diff(sub_m),
         Some(("search", sub_m)) => search(sub_m),
-        _ => cli.print_help().expect("Failed to print help"),
+        _ => Ok(cli.print_help()?),
+    }
+}
+
+// Find the database to use, --db comes before $SEARCHHELP_DB and the data directory
+fn database_path(matches: &ArgMatches) -> Result<PathBuf, SearchHelpError> {
+    if let Some(path) = matches.get_one::<PathBuf>("db") {
+        return Ok(path.clone());
     }
+    if let Some(path) = env::var_os(DATABASE_ENV).filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+
+    let Some(path) = default_database_path() else {
+        return Err(SearchHelpError::InvalidInput(format!(
+            "Could not find a data directory for the database, use --db or set {}",
+            DATABASE_ENV
+        )));
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context(format!("Could not create {}", dir.display()))?;
+    }
+
+    // Databases used to be created in the current directory, the first run with the new
+    // location offers to move one over
+    let legacy = Path::new(LEGACY_DATABASE_FILE);
+    if !path.exists() && legacy.is_file() {
+        return offer_database_move(legacy, path);
+    }
+
+    Ok(path)
 }
 
-fn add(sub_m: &ArgMatches) {
+// Without a terminal to ask in, the old database keeps being used until the move is offered
+fn offer_database_move(legacy: &Path, path: PathBuf) -> Result<PathBuf, SearchHelpError> {
+    if !io::stdin().is_terminal() {
+        eprintln!(
+            "Using {} in the current directory, run searchhelp in a terminal to move it to {}",
+            legacy.display(),
+            path.display()
+        );
+        return Ok(legacy.to_path_buf());
+    }
+
+    let choice = print_choice(
+        &format!(
+            "Databases are now stored in {}, move {} from the current directory there?",
+            path.display(),
+            legacy.display()
+        ),
+        vec!["y", "N", ""],
+    )?;
+    if choice == "y" {
+        move_database(legacy, &path).context("Could not move the database")?;
+        println!("Moved the database to {}", path.display());
+    }
+
+    Ok(path)
+}
+
+fn add(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
     let name = sub_m.get_one::<String>("name").expect("Name is required");
-    let command = sub_m
-        .get_one::<String>("command")
-        .expect("Command is required");
+    let command = sub_m.get_one::<String>("command");
 
-    let command_strings = command.split(";").map(|c| c.trim()).collect::<Vec<&str>>();
+    let depth = *sub_m
+        .get_one::<usize>("depth")
+        .expect("Depth has a default");
+    let keep_raw = sub_m.get_flag("keep-raw");
 
-    let conn = open_connection().expect("Error opening database");
-    let mut programs = Program::get_all(&conn).expect("Error getting programs");
+    let mut commands = match command.map(|c| HelpCommand::parse_list(c, sub_m.get_flag("shell"))) {
+        Some(Ok(mut commands)) => {
+            for command in commands.iter_mut() {
+                command.timeout_secs = sub_m.get_one::<u64>("timeout").copied();
+                command.max_output = sub_m.get_one::<usize>("max-output").copied();
+            }
+            commands
+        }
+        Some(Err(e)) => {
+            return Err(SearchHelpError::InvalidInput(format!(
+                "Invalid command \"{}\": {}",
+                command.unwrap(),
+                e
+            )));
+        }
+        None => Vec::new(),
+    };
+
+    // Man pages of subcommands are usually named like "git-commit"
+    if sub_m.get_flag("man") {
+        commands.push(HelpCommand::man_page(&name.replace(' ', "-")));
+    }
+
+    let mut conn = open_connection()?;
+    if let Some(id) = Alias::get_program_id(&conn, name)? {
+        let program = Program::get_by_id(&conn, id)?;
+        return Err(SearchHelpError::InvalidInput(format!(
+            "{} is an alias of {}, remove it first to add a program with that name",
+            name,
+            program.map_or_else(|| id.to_string(), |p| p.name)
+        )));
+    }
+    let mut programs = Program::get_all(&conn)?;
 
     let current_program_index = programs.iter().position(|p| p.name == *name);
 
@@ -49,101 +196,706 @@ fn add(sub_m: &ArgMatches) {
         let choice = print_choice(
             "Program with that name already exists, do you wish to update it?",
             vec!["y", "N", ""],
+        )?;
+        if choice != "y" {
+            return Ok(());
+        }
+
+        let texts = get_command_texts(&commands).context(format!("Could not update {}", name))?;
+        program.commands = commands.clone();
+        program.help_text = texts.text.clone();
+        program.update(&conn)?;
+
+        let id = program.id.unwrap();
+        store_raw_outputs(&conn, id, &texts.raw_outputs(), keep_raw)?;
+        let binary = store_binary(&conn, id, name, &commands, &mut HashMap::new())?;
+        replace_subcommands(&mut conn, id, name, &texts.outputs, depth, binary.as_ref())?;
+        return Ok(());
+    }
+
+    let texts = get_command_texts(&commands).context(format!("Could not add {}", name))?;
+
+    let program = Program::new(name, commands.clone(), &texts.text);
+    let id = program
+        .insert(&conn)
+        .context(format!("Could not add {}", name))?;
+
+    store_raw_outputs(&conn, id, &texts.raw_outputs(), keep_raw)?;
+    let binary = store_binary(&conn, id, name, &commands, &mut HashMap::new())?;
+    add_subcommands(&conn, id, name, &texts.outputs, depth, binary.as_ref())?;
+    println!("Program added successfully");
+    Ok(())
+}
+
+// Keep the raw output of the help commands when asked to, and drop any kept before otherwise
+fn store_raw_outputs(
+    conn: &Connection,
+    program_id: i32,
+    raw_outputs: &[RawOutput],
+    keep_raw: bool,
+) -> Result<(), SearchHelpError> {
+    let raw_outputs = if keep_raw { raw_outputs } else { &[] };
+    RawOutput::replace_for_program(conn, program_id, raw_outputs)?;
+    Ok(())
+}
+
+// Record the binary behind a program, binaries already looked at in this run are reused
+fn store_binary(
+    conn: &Connection,
+    program_id: i32,
+    name: &str,
+    commands: &[HelpCommand],
+    inspected: &mut HashMap<PathBuf, ProgramBinary>,
+) -> Result<Option<ProgramBinary>, SearchHelpError> {
+    let Some(path) = binary_name(name, commands).and_then(|n| resolve_binary(&n)) else {
+        return Ok(None);
+    };
+
+    let binary = match inspected.get(&path) {
+        Some(binary) => binary.clone(),
+        None => {
+            // A binary that can not be read is left out, the help text was still captured
+            let Ok(mut binary) = inspect_binary(&path) else {
+                return Ok(None);
+            };
+            binary.version_output = probe_version(&path);
+            inspected.insert(path, binary.clone());
+            binary
+        }
+    };
+
+    binary.replace_for_program(conn, program_id)?;
+    Ok(Some(binary))
+}
+
+// Store the help texts of the subcommands of a program as its children, found in the output
+// of its help commands, they share the binary of the program
+// Crawl the subcommands of a program again, replacing the stored ones in one transaction so
+// they are kept when crawling fails. With a depth of 0 they are not crawled and kept as well
+fn replace_subcommands(
+    conn: &mut Connection,
+    program_id: i32,
+    name: &str,
+    outputs: &[(HelpCommand, CommandText)],
+    depth: usize,
+    binary: Option<&ProgramBinary>,
+) -> Result<(), SearchHelpError> {
+    if depth == 0 {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    Program::delete_children(&tx, program_id)?;
+    add_subcommands(&tx, program_id, name, outputs, depth, binary)?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn add_subcommands(
+    conn: &Connection,
+    program_id: i32,
+    name: &str,
+    outputs: &[(HelpCommand, CommandText)],
+    depth: usize,
+    binary: Option<&ProgramBinary>,
+) -> Result<(), SearchHelpError> {
+    if depth == 0 {
+        return Ok(());
+    }
+
+    // Crawl from the first help command that lists subcommands
+    let Some((command, output)) = outputs
+        .iter()
+        .find(|(_, output)| !find_subcommands(&output.text).is_empty())
+    else {
+        println!("No subcommands found");
+        return Ok(());
+    };
+
+    let mut ids: Vec<(Vec<String>, i32)> = Vec::new();
+    let subcommand_texts = get_subcommand_texts(command, &output.text, depth);
+
+    for subcommand in &subcommand_texts {
+        let parent_path = &subcommand.path[..subcommand.path.len() - 1];
+        let parent_id = ids
+            .iter()
+            .find(|(path, _)| path == parent_path)
+            .map_or(program_id, |(_, id)| *id);
+
+        let mut child = Program::new(
+            &format!("{} {}", name, subcommand.path.join(" ")),
+            vec![subcommand.command.clone()],
+            &subcommand.text,
         );
+        child.parent_id = Some(parent_id);
+
+        let id = child.insert(conn)?;
+        if let Some(binary) = binary {
+            binary.replace_for_program(conn, id)?;
+        }
+        ids.push((subcommand.path.clone(), id));
+    }
+
+    println!("Added {} subcommands", subcommand_texts.len());
+    Ok(())
+}
 
-        if let Ok(input) = choice {
-            match input.as_str() {
-                "y" => {
-                    let texts = get_command_texts(&command_strings);
-                    program.help_text = texts;
-                    program.update(&conn).expect("Error updating program");
-                    return;
+fn refresh(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    let conn = open_connection()?;
+    let dry_run = sub_m.get_flag("dry-run");
+    let skip_unchanged = sub_m.get_flag("skip-unchanged");
+
+    let programs = if sub_m.get_flag("all") {
+        Program::get_all(&conn)?
+    } else {
+        let mut programs = Vec::new();
+        for name in sub_m.get_many::<String>("names").into_iter().flatten() {
+            let Some(program) = Program::get_by_name_or_alias(&conn, name)? else {
+                eprintln!("Program {} does not exist", name);
+                continue;
+            };
+
+            let child_ids = Program::get_descendant_ids(&conn, program.id.unwrap())?;
+            programs.push(program);
+            for id in child_ids {
+                programs.extend(Program::get_by_id(&conn, id)?);
+            }
+        }
+        programs
+    };
+
+    let (mut changed, mut unchanged, mut skipped, mut failed) = (0, 0, 0, 0);
+    let mut inspected = HashMap::new();
+
+    for mut program in programs {
+        let id = program.id.unwrap();
+
+        // Help texts can change without the binary being replaced, through configuration or
+        // plugins, so skipping is asked for. Man pages are installed apart from the binary
+        if skip_unchanged && program.commands.iter().all(|c| !c.man) {
+            let stored = ProgramBinary::get_by_program(&conn, id)?;
+            let path =
+                binary_name(&program.name, &program.commands).and_then(|n| resolve_binary(&n));
+            if let (Some(stored), Some(path)) = (&stored, &path) {
+                if binary_unchanged(stored, path) {
+                    println!(
+                        "{}: {}",
+                        program.name.bold(),
+                        "binary unchanged, skipped".dimmed()
+                    );
+                    skipped += 1;
+                    continue;
                 }
-                _ => {
-                    return;
+            }
+        }
+
+        // Only commands that ran leave raw output, a partial help text is not stored
+        let result = get_command_texts(&program.commands);
+        let Some(captured) = result
+            .ok()
+            .filter(|captured| captured.outputs.len() == program.commands.len())
+        else {
+            println!(
+                "{}: {}",
+                program.name.bold(),
+                "failed, kept the stored help text".red()
+            );
+            failed += 1;
+            continue;
+        };
+        let raw_outputs = captured.raw_outputs();
+        let texts = captured.text;
+
+        if !dry_run {
+            store_binary(&conn, id, &program.name, &program.commands, &mut inspected)?;
+        }
+
+        if texts == program.help_text {
+            println!("{}: {}", program.name.bold(), "unchanged".dimmed());
+            unchanged += 1;
+            continue;
+        }
+
+        let (added, removed) = count_changes(&diff_lines(&program.help_text, &texts));
+        println!(
+            "{}: {} {} lines",
+            program.name.bold(),
+            format!("+{}", added).green(),
+            format!("-{}", removed).red()
+        );
+
+        let options = option_changes(&program.help_text, &texts);
+        if !options.added.is_empty() {
+            println!("    new options: {}", options.added.join(", ").green());
+        }
+        if !options.removed.is_empty() {
+            println!("    removed options: {}", options.removed.join(", ").red());
+        }
+        changed += 1;
+
+        if dry_run {
+            continue;
+        }
+
+        program.help_text = texts;
+        program.update(&conn)?;
+        if RawOutput::has_program(&conn, id)? {
+            store_raw_outputs(&conn, id, &raw_outputs, true)?;
+        }
+    }
+
+    println!(
+        "\n{} {}, {} unchanged, {} skipped, {} failed",
+        changed,
+        if dry_run {
+            "would be updated"
+        } else {
+            "updated"
+        },
+        unchanged,
+        skipped,
+        failed
+    );
+    Ok(())
+}
+
+fn alias(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    let conn = open_connection()?;
+
+    match sub_m.subcommand() {
+        Some(("add", sub_m)) => {
+            let name = sub_m.get_one::<String>("name").expect("Name is required");
+            let Some(program) = Program::get_by_name_or_alias(&conn, name)? else {
+                return Err(SearchHelpError::ProgramNotFound(name.to_string()));
+            };
+
+            for alias in sub_m
+                .get_many::<String>("aliases")
+                .expect("Aliases are required")
+            {
+                // A name can only refer to one program
+                if let Some(other) = Program::get_by_name_or_alias(&conn, alias)? {
+                    eprintln!("{} already refers to {}, skipped", alias, other.name);
+                    continue;
+                }
+
+                Alias {
+                    alias: alias.to_string(),
+                    program_id: program.id.unwrap(),
+                }
+                .insert(&conn)?;
+                println!("{} now refers to {}", alias, program.name);
+            }
+        }
+        Some(("remove", sub_m)) => {
+            for alias in sub_m
+                .get_many::<String>("aliases")
+                .expect("Aliases are required")
+            {
+                if !Alias::delete(&conn, alias)? {
+                    eprintln!("{} is not an alias", alias);
                 }
             }
         }
+        Some(("list", sub_m)) => {
+            let program_id = match sub_m.get_one::<String>("name") {
+                Some(name) => match Program::get_by_name_or_alias(&conn, name)? {
+                    Some(program) => program.id,
+                    None => {
+                        return Err(SearchHelpError::ProgramNotFound(name.to_string()));
+                    }
+                },
+                None => None,
+            };
+
+            let names = Program::get_all(&conn)?
+                .into_iter()
+                .map(|p| (p.id.unwrap(), p.name))
+                .collect::<HashMap<i32, String>>();
+            for alias in Alias::get_all(&conn)? {
+                if program_id.is_none_or(|id| id == alias.program_id) {
+                    println!("{} -> {}", alias.alias, names[&alias.program_id]);
+                }
+            }
+        }
+        _ => {}
     }
+    Ok(())
+}
 
-    let texts = get_command_texts(&command_strings);
+fn tag(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    let conn = open_connection()?;
 
-    let program = Program::new(
-        name,
-        command_strings
-            .iter()
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>(),
-        &texts,
+    if let Some((action, sub_m)) = sub_m.subcommand() {
+        let name = sub_m.get_one::<String>("name").expect("Name is required");
+        let Some(program) = Program::get_by_name_or_alias(&conn, name)? else {
+            return Err(SearchHelpError::ProgramNotFound(name.to_string()));
+        };
+
+        let id = program.id.unwrap();
+        for tag in sub_m.get_many::<String>("tags").expect("Tags are required") {
+            let (changed, unchanged_message) = if action == "add" {
+                (ProgramTag::insert(&conn, id, tag)?, "is already tagged")
+            } else {
+                (ProgramTag::delete(&conn, id, tag)?, "is not tagged")
+            };
+            if !changed {
+                eprintln!("{} {} {}", program.name, unchanged_message, tag);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn list_programs(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    ColorChoice::Auto.apply();
+    let conn = open_connection()?;
+
+    let mut programs = ProgramSummary::get_all(&conn)?;
+    if let Some(filter) = sub_m.get_one::<String>("filter") {
+        let pattern = Pattern::new(filter)
+            .map_err(|e| SearchHelpError::InvalidInput(format!("Invalid filter: {}", e)))?;
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..MatchOptions::new()
+        };
+        programs.retain(|p| pattern.matches_with(&p.name, options));
+    }
+
+    // Programs are sorted by name already, the other orders keep it for ties
+    match sub_m.get_one::<String>("sort").map(String::as_str) {
+        Some("updated") => programs.sort_by(|a, b| b.updated_at.cmp(&a.updated_at)),
+        Some("size") => programs.sort_by_key(|p| std::cmp::Reverse(p.size)),
+        _ => {}
+    }
+
+    match sub_m.get_one::<String>("format").map(String::as_str) {
+        Some("json") => {
+            let json = serde_json::to_string_pretty(&programs).map_err(io::Error::from)?;
+            println!("{}", json);
+        }
+        Some("csv") => {
+            println!("name,commands,size,added_at,updated_at,tags");
+            for program in &programs {
+                println!(
+                    "{},{},{},{},{},{}",
+                    csv_field(&program.name),
+                    program.commands,
+                    program.size,
+                    program.added_at.as_deref().unwrap_or_default(),
+                    program.updated_at.as_deref().unwrap_or_default(),
+                    csv_field(&program.tags.join(";"))
+                );
+            }
+        }
+        Some("names") => {
+            for program in &programs {
+                println!("{}", program.name);
+            }
+        }
+        _ => print_program_table(&programs),
+    }
+    Ok(())
+}
+
+fn print_program_table(programs: &[ProgramSummary]) {
+    let name_width = programs
+        .iter()
+        .map(|p| p.name.chars().count())
+        .chain(std::iter::once("NAME".len()))
+        .max()
+        .unwrap_or_default();
+
+    // The header is padded before it is colored, so the escape codes do not count as width
+    let header = format!(
+        "{:<name_width$}  {:>8}  {:>8}  {:<19}  {:<19}  {}",
+        "NAME", "COMMANDS", "SIZE", "ADDED", "UPDATED", "TAGS"
     );
-    let result = program.insert(&conn);
+    println!("{}", header.bold());
+    for program in programs {
+        let row = format!(
+            "{:<name_width$}  {:>8}  {:>8}  {:<19}  {:<19}  {}",
+            program.name,
+            program.commands,
+            program.size,
+            program.added_at.as_deref().unwrap_or("-"),
+            program.updated_at.as_deref().unwrap_or("-"),
+            program.tags.join(", ")
+        );
+        // Programs without tags would end in spaces
+        println!("{}", row.trim_end());
+    }
+}
 
-    match result {
-        Ok(_) => {
-            println!("Program added successfully");
+// Quote a CSV field when it contains a separator, a quote or a line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn show(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    let conn = open_connection()?;
+    let name = sub_m.get_one::<String>("name").expect("Name is required");
+
+    let Some(program) = Program::get_by_name_or_alias(&conn, name)? else {
+        return Err(SearchHelpError::ProgramNotFound(name.to_string()));
+    };
+    show_text(&program.name, &program.help_text)?;
+    Ok(())
+}
+
+fn info(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    ColorChoice::Auto.apply();
+    let conn = open_connection()?;
+    let name = sub_m.get_one::<String>("name").expect("Name is required");
+
+    let Some(program) = Program::get_by_name_or_alias(&conn, name)? else {
+        return Err(SearchHelpError::ProgramNotFound(name.to_string()));
+    };
+    let id = program.id.unwrap();
+
+    println!("{}", program.name.bold());
+    let aliases = Alias::get_by_program(&conn, id)?;
+    if !aliases.is_empty() {
+        println!("{:<16}{}", "Aliases", aliases.join(", "));
+    }
+    let tags = ProgramTag::get_by_program(&conn, id)?;
+    if !tags.is_empty() {
+        println!("{:<16}{}", "Tags", tags.join(", "));
+    }
+    if let Some(parent_id) = program.parent_id {
+        if let Some(parent) = Program::get_by_id(&conn, parent_id)? {
+            println!("{:<16}{}", "Subcommand of", parent.name);
         }
-        Err(e) => {
-            println!("Error adding program: {}", e);
+    }
+    for (index, command) in program.commands.iter().enumerate() {
+        let label = if index == 0 { "Commands" } else { "" };
+        println!("{:<16}{}", label, command);
+    }
+    println!(
+        "{:<16}{} lines, {} options",
+        "Help text",
+        program.help_text.lines().count(),
+        ProgramOption::get_by_program(&conn, id)?.len()
+    );
+
+    let revisions = ProgramRevision::get_by_program(&conn, id)?;
+    if let Some(latest) = revisions.last() {
+        println!(
+            "{:<16}{}, latest from {}",
+            "Revisions",
+            revisions.len(),
+            latest.created_at
+        );
+    }
+
+    let Some(binary) = ProgramBinary::get_by_program(&conn, id)? else {
+        println!("{:<16}{}", "Binary", "not recorded".dimmed());
+        return Ok(());
+    };
+    println!("{:<16}{}", "Binary", binary.path);
+    println!(
+        "{:<16}{} bytes, modified {}",
+        "",
+        binary.size,
+        binary.modified_at(&conn)?
+    );
+    println!("{:<16}{}", "Hash", binary.hash);
+    println!(
+        "{:<16}{}",
+        "Checked",
+        binary.checked_at.as_deref().unwrap_or_default()
+    );
+    match &binary.version_output {
+        Some(version) => {
+            for (index, line) in version.lines().enumerate() {
+                let label = if index == 0 { "Version" } else { "" };
+                println!("{:<16}{}", label, line);
+            }
         }
+        None => println!("{:<16}{}", "Version", "no version probe succeeded".dimmed()),
     }
+    Ok(())
 }
 
-fn update(sub_m: &ArgMatches) {
-    let conn = open_connection().expect("Error opening database");
+fn history(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    ColorChoice::Auto.apply();
+    let conn = open_connection()?;
+    let name = sub_m.get_one::<String>("name").expect("Name is required");
+
+    let Some(program) = Program::get_by_name_or_alias(&conn, name)? else {
+        return Err(SearchHelpError::ProgramNotFound(name.to_string()));
+    };
+    let revisions = ProgramRevision::get_by_program(&conn, program.id.unwrap())?;
+
+    println!("{}", program.name.bold());
+    let mut previous: Option<&ProgramRevision> = None;
+    for revision in &revisions {
+        let changes = match previous {
+            Some(previous) => {
+                let (added, removed) =
+                    count_changes(&diff_lines(&previous.help_text, &revision.help_text));
+                let options = option_changes(&previous.help_text, &revision.help_text);
+                format!(
+                    "+{} -{} lines, {} new and {} removed options",
+                    added,
+                    removed,
+                    options.added.len(),
+                    options.removed.len()
+                )
+            }
+            None => format!("{} lines", revision.help_text.lines().count()),
+        };
+
+        println!(
+            "{:>4}  {}  {:<12}  {}",
+            revision.number.to_string().yellow(),
+            revision.created_at.dimmed(),
+            revision.version.as_deref().unwrap_or("-"),
+            changes
+        );
+        previous = Some(revision);
+    }
+    Ok(())
+}
+
+fn diff(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    ColorChoice::Auto.apply();
+    let conn = open_connection()?;
+    let name = sub_m.get_one::<String>("name").expect("Name is required");
+
+    let Some(program) = Program::get_by_name_or_alias(&conn, name)? else {
+        return Err(SearchHelpError::ProgramNotFound(name.to_string()));
+    };
+    let revisions = ProgramRevision::get_by_program(&conn, program.id.unwrap())?;
+
+    let latest = revisions.len();
+    let rev_a = sub_m.get_one::<usize>("rev-a").copied();
+    let rev_b = sub_m.get_one::<usize>("rev-b").copied();
+    let (rev_a, rev_b) = match (rev_a, rev_b) {
+        (Some(a), Some(b)) => (a, b),
+        (Some(a), None) => (a, latest),
+        _ => (latest.saturating_sub(1).max(1), latest),
+    };
+
+    for number in [rev_a, rev_b] {
+        if number == 0 || number > latest {
+            return Err(SearchHelpError::InvalidInput(format!(
+                "Revision {} does not exist, {} has revisions 1 to {}",
+                number, program.name, latest
+            )));
+        }
+    }
+
+    let old = &revisions[rev_a - 1];
+    let new = &revisions[rev_b - 1];
+    let lines = diff_lines(&old.help_text, &new.help_text);
+    if count_changes(&lines) == (0, 0) {
+        println!("No differences between revisions {} and {}", rev_a, rev_b);
+        return Ok(());
+    }
+
+    let label = |revision: &ProgramRevision| {
+        let version = match &revision.version {
+            Some(version) => format!(", version {}", version),
+            None => String::new(),
+        };
+        format!(
+            "{} revision {} ({}{})",
+            program.name, revision.number, revision.created_at, version
+        )
+    };
+    print_unified(&label(old), &label(new), &lines, 3);
+
+    // Parsed options give a shorter summary of what a new version added or dropped
+    let options = option_changes(&old.help_text, &new.help_text);
+    if !options.added.is_empty() || !options.removed.is_empty() {
+        println!();
+        if !options.added.is_empty() {
+            println!("New options: {}", options.added.join(", ").green());
+        }
+        if !options.removed.is_empty() {
+            println!("Removed options: {}", options.removed.join(", ").red());
+        }
+    }
+    Ok(())
+}
+
+fn update(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    let conn = open_connection()?;
 
     let program = sub_m.get_one::<String>("name");
 
     match program {
         Some(name) => {
-            let program = Program::get_by_name(&conn, name).expect("Error getting program");
-
-            match program {
-                Some(program) => {
-                    update_program(&program);
-                }
-                None => {
-                    println!("Program with that name does not exist");
-                }
-            }
+            let program = Program::get_by_name_or_alias(&conn, name)?
+                .ok_or_else(|| SearchHelpError::ProgramNotFound(name.to_string()))?;
+            edit_and_update(&program)?;
         }
         None => {
-            let programs = Program::get_all(&conn).expect("Error getting programs");
+            let programs = Program::get_all(&conn)?;
 
             let list_items = programs
                 .iter()
-                .map(|p| ListItem::new(p.name.as_str(), p, |p| update_program(*p)))
+                .filter(|p| p.parent_id.is_none())
+                .map(|p| ListItem::new(p.name.as_str(), p, |p| update_program(p)))
                 .collect::<Vec<ListItem<&Program>>>();
 
             print_list(
                 list_items,
                 ListSettings::new(ListIndicator::Bullet, |s| s.yellow(), |s| s.blue()),
-            )
-            .expect("Error printing list");
+            )?;
         }
     }
+    Ok(())
 }
 
+// List callbacks can not return errors, they are reported and the list stays open
 fn update_program(item: &Program) -> bool {
-    let conn = open_connection().expect("Error opening database");
-    let program = Program::get_by_id(&conn, item.id.unwrap()).expect("Error getting program");
-    if let Some(mut program) = program {
-        let result = edit_program(&mut program);
-        match result {
-            Ok(true) => {
-                program.update(&conn).expect("Error updating program");
-            }
-            Ok(false) => {
-                println!("Program update canceled.");
-            }
-            Err(e) => {
-                println!("Error editing program: {}", e);
-            }
-        }
+    if let Err(e) = edit_and_update(item) {
+        report(&e);
     }
-
     false
 }
 
+fn edit_and_update(item: &Program) -> Result<(), SearchHelpError> {
+    let conn = open_connection()?;
+    let Some(mut program) = Program::get_by_id(&conn, item.id.unwrap())? else {
+        return Ok(());
+    };
+    let commands = program.commands.clone();
+
+    if !edit_program(&mut program).context("Could not edit the program")? {
+        println!("Program update canceled.");
+        return Ok(());
+    }
+    if let Some(taken_by) = name_taken(&conn, &program)? {
+        println!(
+            "Program update canceled, {} is already used by {}.",
+            program.name, taken_by
+        );
+        return Ok(());
+    }
+    program.update(&conn)?;
+
+    // The binary is recorded again by the next refresh, which runs the new commands
+    if program.commands != commands {
+        ProgramBinary::delete_by_program(&conn, program.id.unwrap())?;
+    }
+    Ok(())
+}
+
+// Find the program already using a name, as its name or as an alias
+fn name_taken(conn: &Connection, program: &Program) -> Result<Option<String>, SearchHelpError> {
+    let other = Program::get_by_name_or_alias(conn, &program.name)?;
+    Ok(other
+        .filter(|other| other.id != program.id)
+        .map(|other| other.name))
+}
+
 enum EditField {
     Name,
     Commands,
@@ -158,7 +910,12 @@ fn edit_program(program: &mut Program) -> Result<bool, std::io::Error> {
     execute!(stdout, cursor::Show)?;
     execute!(stdout, cursor::SetCursorStyle::BlinkingBar)?;
 
-    let mut commands = program.commands.join("; ");
+    let mut commands = program
+        .commands
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>()
+        .join("; ");
 
     loop {
         // Clear the screen and display the fields
@@ -249,19 +1006,17 @@ fn edit_program(program: &mut Program) -> Result<bool, std::io::Error> {
             event::Event::Key(event::KeyEvent {
                 code: event::KeyCode::Backspace,
                 ..
-            }) => {
+            }) if cursor_pos > 0 => {
                 // Handle backspace to remove characters
-                if cursor_pos > 0 {
-                    match current_field {
-                        EditField::Name => {
-                            program.name.remove(cursor_pos - 1);
-                        }
-                        EditField::Commands => {
-                            commands.remove(cursor_pos - 1);
-                        }
+                match current_field {
+                    EditField::Name => {
+                        program.name.remove(cursor_pos - 1);
+                    }
+                    EditField::Commands => {
+                        commands.remove(cursor_pos - 1);
                     }
-                    cursor_pos -= 1;
                 }
+                cursor_pos -= 1;
             }
             event::Event::Key(event::KeyEvent {
                 code: event::KeyCode::Delete,
@@ -329,9 +1084,7 @@ fn edit_program(program: &mut Program) -> Result<bool, std::io::Error> {
                 ..
             }) => {
                 // Move cursor left
-                if cursor_pos > 0 {
-                    cursor_pos -= 1;
-                }
+                cursor_pos = cursor_pos.saturating_sub(1);
             }
             event::Event::Key(event::KeyEvent {
                 code: event::KeyCode::Right,
@@ -359,56 +1112,197 @@ fn edit_program(program: &mut Program) -> Result<bool, std::io::Error> {
     terminal::disable_raw_mode()?;
     execute!(stdout, terminal::LeaveAlternateScreen)?;
 
-    program.commands = commands
-        .split(';')
-        .map(|s| s.trim().to_string())
-        .collect::<Vec<String>>();
+    let edited = HelpCommand::parse_list(&commands, false)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+    // The limits are not part of the edited text, commands that were not changed keep theirs
+    program.commands = edited
+        .into_iter()
+        .map(|mut command| {
+            let text = command.to_string();
+            if let Some(old) = program.commands.iter().find(|c| c.to_string() == text) {
+                command.timeout_secs = old.timeout_secs;
+                command.max_output = old.max_output;
+            }
+            command
+        })
+        .collect();
 
     Ok(true)
 }
 
-fn delete(sub_m: &ArgMatches) {
-    let conn = open_connection().expect("Error opening database");
+fn delete(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
+    let conn = open_connection()?;
 
     let program = sub_m.get_one::<String>("name");
 
     match program {
         Some(name) => {
-            let program = Program::get_by_name(&conn, name).expect("Error getting program");
-
-            match program {
-                Some(program) => {
-                    Program::delete(&conn, program.id.unwrap()).expect("Error deleting program");
-                }
-                None => {
-                    println!("Program with that name does not exist");
-                }
-            }
+            let program = Program::get_by_name_or_alias(&conn, name)?
+                .ok_or_else(|| SearchHelpError::ProgramNotFound(name.to_string()))?;
+            Program::delete(&conn, program.id.unwrap())?;
         }
         None => {
-            let programs = Program::get_all(&conn).expect("Error getting programs");
+            let programs = Program::get_all(&conn)?;
 
             let list_items = programs
                 .iter()
-                .map(|p| ListItem::new(p.name.as_str(), p, |p| delete_from_list(*p)))
+                .filter(|p| p.parent_id.is_none())
+                .map(|p| ListItem::new(p.name.as_str(), p, |p| delete_from_list(p)))
                 .collect::<Vec<ListItem<&Program>>>();
 
             print_list(
                 list_items,
                 ListSettings::new(ListIndicator::Bullet, |s| s.yellow(), |s| s.blue()),
-            )
-            .expect("Error printing list");
+            )?;
         }
     }
+    Ok(())
 }
 
 fn delete_from_list(item: &Program) -> bool {
-    let conn = open_connection().expect("Error opening database");
-    Program::delete(&conn, item.id.unwrap()).expect("Error deleting program");
-    true
+    let result = open_connection().and_then(|conn| Program::delete(&conn, item.id.unwrap()));
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            report(&SearchHelpError::from(e));
+            false
+        }
+    }
 }
 
-fn search(sub_m: &ArgMatches) {
+fn search(sub_m: &ArgMatches) -> Result<(), SearchHelpError> {
     let query = sub_m.get_one::<String>("query").expect("Query is required");
-    println!("Searching for: {}", query);
+    ColorChoice::from_arg(
+        sub_m
+            .get_one::<String>("color")
+            .expect("Color has a default"),
+    )
+    .apply();
+
+    let conn = open_connection()?;
+    let program_ids = match sub_m.get_many::<String>("program") {
+        Some(names) => Some(resolve_programs(
+            &conn,
+            &names.cloned().collect::<Vec<String>>(),
+        )?),
+        None => None,
+    };
+
+    // Without explicit context options the whole option block around a match is shown
+    let before = sub_m.get_one::<usize>("before-context");
+    let after = sub_m.get_one::<usize>("after-context");
+    let around = sub_m.get_one::<usize>("context");
+    let context = if before.is_some() || after.is_some() || around.is_some() {
+        ContextMode::Lines {
+            before: *before.or(around).unwrap_or(&0),
+            after: *after.or(around).unwrap_or(&0),
+        }
+    } else {
+        ContextMode::Paragraph
+    };
+
+    let options = SearchOptions {
+        fuzzy: sub_m.get_flag("fuzzy"),
+        options: sub_m.get_flag("options"),
+        regex: sub_m.get_flag("regex"),
+        case_sensitive: sub_m.get_flag("case-sensitive"),
+        word: sub_m.get_flag("word"),
+        program_ids,
+        sections: sub_m
+            .get_many::<String>("section")
+            .map(|kinds| kinds.map(|k| SectionKind::from_arg(k)).collect()),
+        context,
+    };
+
+    let matcher = Matcher::new(query, &options)
+        .map_err(|e| SearchHelpError::InvalidInput(format!("Invalid search pattern: {}", e)))?;
+
+    if options.options {
+        let results = search_program_options(&conn, query, &matcher, &options)?;
+
+        if results.is_empty() {
+            return Err(SearchHelpError::NoMatches(format!(
+                "No options found for \"{}\"",
+                query
+            )));
+        }
+
+        print_option_results(&results);
+        return Ok(());
+    }
+
+    let format = OutputFormat::from_arg(
+        sub_m
+            .get_one::<String>("format")
+            .expect("Format has a default"),
+    );
+    let results = search_programs(&conn, query, &matcher, &options)?;
+
+    // Scripts still get valid JSON when nothing matched, the exit code tells them why it is empty
+    match format {
+        OutputFormat::Text => print_results(&results, &options),
+        _ => print_json_results(&results, &format)?,
+    }
+
+    if results.is_empty() {
+        return Err(SearchHelpError::NoMatches(format!(
+            "No matches found for \"{}\"",
+            query
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use command::OutputStream;
+    use db::open_test_connection;
+    use std::process::ExitStatus;
+
+    // A program with one stored subcommand, and help output that lists no subcommands
+    fn program_with_subcommand(conn: &Connection) -> (i32, Vec<(HelpCommand, CommandText)>) {
+        let command = HelpCommand::parse("tool --help", false).unwrap();
+        let id = Program::new("tool", vec![command.clone()], "Usage: tool\n")
+            .insert(conn)
+            .unwrap();
+        let mut child = Program::new("tool run", Vec::new(), "Usage: tool run\n");
+        child.parent_id = Some(id);
+        child.insert(conn).unwrap();
+
+        let output = CommandText {
+            text: "Usage: tool\n".to_string(),
+            stream: OutputStream::Stdout,
+            status: ExitStatus::default(),
+            raw: RawOutput {
+                command: command.to_string(),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            },
+        };
+        (id, vec![(command, output)])
+    }
+
+    #[test]
+    fn keeps_subcommands_when_they_are_not_crawled() {
+        let mut conn = open_test_connection();
+        let (id, outputs) = program_with_subcommand(&conn);
+
+        replace_subcommands(&mut conn, id, "tool", &outputs, 0, None).unwrap();
+        assert_eq!(Program::get_descendant_ids(&conn, id).unwrap().len(), 1);
+
+        replace_subcommands(&mut conn, id, "tool", &outputs, 1, None).unwrap();
+        assert!(Program::get_descendant_ids(&conn, id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn quotes_csv_fields_only_when_needed() {
+        assert_eq!(csv_field("ls"), "ls");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+        assert_eq!(csv_field(""), "");
+    }
 }
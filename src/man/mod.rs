@@ -0,0 +1,163 @@
+pub mod roff;
+
+use flate2::read::GzDecoder;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+// Used when MANPATH is not set, or for the empty entries in it
+const DEFAULT_MAN_DIRS: [&str; 4] = [
+    "/usr/local/share/man",
+    "/usr/share/man",
+    "/usr/local/man",
+    "/opt/homebrew/share/man",
+];
+
+// The order `man` looks through the sections in
+const SECTION_ORDER: [&str; 11] = ["1", "8", "6", "2", "3", "4", "5", "7", "9", "n", "l"];
+
+// Pages that only redirect to another page with `.so` are followed this many times
+const MAX_REDIRECTS: usize = 5;
+
+// Get the directories to look for man pages in
+pub fn man_dirs() -> Vec<PathBuf> {
+    let defaults = || DEFAULT_MAN_DIRS.iter().map(PathBuf::from);
+
+    match env::var("MANPATH") {
+        Ok(manpath) if !manpath.is_empty() => {
+            let mut dirs = Vec::new();
+            for entry in manpath.split(':') {
+                if entry.is_empty() {
+                    dirs.extend(defaults());
+                } else {
+                    dirs.push(PathBuf::from(entry));
+                }
+            }
+            dirs
+        }
+        _ => defaults().collect(),
+    }
+}
+
+// Find the file of a man page, e.g. "tar" finds man1/tar.1.gz
+pub fn find_page(page: &str) -> Option<PathBuf> {
+    let dirs = man_dirs();
+
+    for section in SECTION_ORDER {
+        for dir in &dirs {
+            if let Some(path) = find_in_section(dir, page, section) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+// Look through the man directories of a section, e.g. man1 and man1p for section 1
+fn find_in_section(dir: &Path, page: &str, section: &str) -> Option<PathBuf> {
+    let mut section_dirs: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("man"))
+                .is_some_and(|suffix| suffix.starts_with(section))
+        })
+        .collect();
+    // Prefer man1 over man1p
+    section_dirs.sort();
+
+    for section_dir in section_dirs {
+        let Ok(entries) = fs::read_dir(&section_dir) else {
+            continue;
+        };
+        let mut matches: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| is_page_file(name, page, section))
+            })
+            .collect();
+        matches.sort();
+
+        if let Some(path) = matches.into_iter().next() {
+            return Some(path);
+        }
+    }
+    None
+}
+
+// Check a file name like "tar.1.gz" or "openssl.1ssl" against a page and section
+fn is_page_file(file_name: &str, page: &str, section: &str) -> bool {
+    let file_name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+    file_name
+        .strip_prefix(page)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .is_some_and(|suffix| suffix.starts_with(section))
+}
+
+// Read the roff source of a man page, decompressing it and following `.so` redirects
+pub fn read_page(path: &Path) -> io::Result<Vec<u8>> {
+    let mut path = path.to_path_buf();
+
+    for _ in 0..MAX_REDIRECTS {
+        let source = read_file(&path)?;
+
+        let text = String::from_utf8_lossy(&source);
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+        let redirect = match (lines.next(), lines.next()) {
+            (Some(line), None) => line.strip_prefix(".so ").map(str::trim),
+            _ => None,
+        };
+
+        match redirect.and_then(|target| resolve_redirect(&path, target)) {
+            Some(target) => path = target,
+            None => return Ok(source),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("too many .so redirects in {}", path.display()),
+    ))
+}
+
+// `.so man1/other.1` is relative to the directory holding man1
+fn resolve_redirect(path: &Path, target: &str) -> Option<PathBuf> {
+    let root = path.parent()?.parent()?;
+    let target = root.join(target);
+    let compressed = PathBuf::from(format!("{}.gz", target.display()));
+
+    [target, compressed].into_iter().find(|p| p.is_file())
+}
+
+fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut source = Vec::new();
+    let file = File::open(path)?;
+
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        GzDecoder::new(file).read_to_end(&mut source)?;
+    } else {
+        io::BufReader::new(file).read_to_end(&mut source)?;
+    }
+    Ok(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_page_file_names() {
+        assert!(is_page_file("tar.1.gz", "tar", "1"));
+        assert!(is_page_file("tar.1", "tar", "1"));
+        assert!(is_page_file("openssl.1ssl", "openssl", "1"));
+        assert!(!is_page_file("tar.5.gz", "tar", "1"));
+        assert!(!is_page_file("tarcat.1.gz", "tar", "1"));
+    }
+}
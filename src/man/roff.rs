@@ -0,0 +1,776 @@
+// A small renderer for man pages written with the man(7) macros, it produces plain text laid
+// out like `man` does on a terminal so the help parser and search work on it unchanged
+
+use std::collections::HashMap;
+
+// Columns of the rendered page
+const WIDTH: usize = 80;
+// Indent of the text below a section heading
+const BASE_INDENT: usize = 7;
+// Indent of subsection headings
+const SUBSECTION_INDENT: usize = 3;
+// Indent of the body of a tagged paragraph when .TP or .IP do not set one
+const DEFAULT_TAG_WIDTH: usize = 7;
+// Stands in for unbreakable spaces until the words are laid out
+const UNBREAKABLE_SPACE: char = '\u{a0}';
+// Macros calling other macros are expanded this deep
+const MAX_MACRO_DEPTH: usize = 10;
+
+// Render the roff source of a man page to plain text
+pub fn render(source: &str) -> String {
+    let mut renderer = Renderer::default();
+    for line in source.lines() {
+        renderer.line(line);
+    }
+    renderer.finish()
+}
+
+#[derive(Default)]
+struct Renderer {
+    output: String,
+    // Words of the paragraph being filled
+    words: Vec<String>,
+    // Tag printed in front of the current paragraph, e.g. the flags of an option
+    tag: Option<String>,
+    // The next line of text is the tag of a .TP paragraph
+    expect_tag: bool,
+    // The next line of text is a heading, for .SH and .SS without arguments
+    expect_heading: Option<usize>,
+    // Indent set by .RS, and the ones it replaced
+    base_indent: usize,
+    indent_stack: Vec<usize>,
+    // Extra indent of the current paragraph, e.g. the body of a tagged paragraph
+    paragraph_indent: usize,
+    tag_width: usize,
+    no_fill: bool,
+    // Right after a heading blank lines are left out, like the no-space mode of roff
+    no_space: bool,
+    // The last line ended in \c and continues on the next one
+    join_next: bool,
+    // Macros defined by the page, like .Vb in pages made by pod2man
+    macros: HashMap<String, Vec<String>>,
+    // Name of the macro being defined until "..", None for .ig blocks
+    defining: Option<Option<String>>,
+    macro_depth: usize,
+    // Depth of \{ blocks being skipped because their condition is false
+    skip_depth: usize,
+    // Result of the last .ie for its .el
+    last_condition: bool,
+    url: Option<String>,
+    // Printed below the page like `man` does, it names the version of the tool
+    footer: Option<String>,
+}
+
+impl Renderer {
+    fn line(&mut self, line: &str) {
+        if let Some(name) = &self.defining {
+            if line.trim_start().starts_with("..") {
+                self.defining = None;
+            } else if let Some(name) = name {
+                self.macros
+                    .entry(name.clone())
+                    .or_default()
+                    .push(line.to_string());
+            }
+            return;
+        }
+
+        if self.skip_depth > 0 {
+            self.skip_depth += line.matches("\\{").count();
+            self.skip_depth = self.skip_depth.saturating_sub(line.matches("\\}").count());
+            return;
+        }
+
+        match line.strip_prefix('.').or_else(|| line.strip_prefix('\'')) {
+            Some(request) => self.request(request),
+            None => self.text(line),
+        }
+    }
+
+    fn request(&mut self, request: &str) {
+        let request = request.trim_start();
+        let (name, rest) = request
+            .split_once(|c: char| c.is_whitespace())
+            .unwrap_or((request, ""));
+        let args = split_args(rest);
+        let arg = |i: usize| args.get(i).map(String::as_str);
+
+        match name {
+            "if" => self.condition(rest, false),
+            "ie" => self.condition(rest, true),
+            "el" => {
+                let condition = !self.last_condition;
+                self.conditional_body(rest.trim_start(), condition);
+            }
+            "TH" => {
+                let title = match (arg(0), arg(1)) {
+                    (Some(title), Some(section)) => format!("{}({})", title, section),
+                    (Some(title), None) => title.to_string(),
+                    _ => String::new(),
+                };
+                let footer = [
+                    arg(3).unwrap_or_default(),
+                    arg(2).unwrap_or_default(),
+                    &title,
+                ]
+                .iter()
+                .map(|part| render_escapes(part).trim().to_string())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<String>>()
+                .join("  ");
+                self.footer = Some(footer).filter(|footer| !footer.is_empty());
+            }
+            "de" | "de1" | "am" => {
+                let macro_name = arg(0).unwrap_or_default().to_string();
+                if name != "am" {
+                    self.macros.remove(&macro_name);
+                }
+                self.defining = Some(Some(macro_name));
+            }
+            "ig" => self.defining = Some(None),
+            "SH" | "SS" => {
+                let indent = if name == "SH" { 0 } else { SUBSECTION_INDENT };
+                self.reset_indent();
+                if args.is_empty() {
+                    self.expect_heading = Some(indent);
+                } else {
+                    self.heading(&render_escapes(&args.join(" ")), indent);
+                }
+            }
+            "PP" | "LP" | "P" | "HP" => {
+                self.flush();
+                self.blank_line();
+                self.paragraph_indent = 0;
+                self.tag_width = DEFAULT_TAG_WIDTH;
+            }
+            "TP" | "TQ" => {
+                self.flush();
+                if name == "TP" {
+                    self.blank_line();
+                    self.tag_width = arg(0).and_then(parse_width).unwrap_or(self.tag_width());
+                }
+                self.paragraph_indent = self.tag_width();
+                self.expect_tag = true;
+            }
+            "IP" => {
+                self.flush();
+                self.blank_line();
+                self.tag_width = arg(1).and_then(parse_width).unwrap_or(self.tag_width());
+                self.paragraph_indent = self.tag_width();
+                self.tag = arg(0)
+                    .map(render_escapes)
+                    .filter(|tag| !tag.trim().is_empty());
+            }
+            "RS" => {
+                self.flush();
+                self.indent_stack.push(self.base_indent);
+                let width = arg(0).and_then(parse_width).unwrap_or(self.tag_width());
+                self.base_indent += width;
+                self.paragraph_indent = 0;
+            }
+            "RE" => {
+                self.flush();
+                self.base_indent = self.indent_stack.pop().unwrap_or(0);
+                self.paragraph_indent = 0;
+            }
+            "br" => self.flush(),
+            "sp" => {
+                self.flush();
+                let lines = arg(0).and_then(parse_width).unwrap_or(1);
+                for _ in 0..lines.max(1) {
+                    self.push_blank_line();
+                }
+            }
+            "nf" | "EX" => {
+                self.flush();
+                self.no_fill = true;
+            }
+            "fi" | "EE" => {
+                self.flush();
+                self.no_fill = false;
+            }
+            // Fonts do not show in plain text, only the words are kept
+            "B" | "I" | "SM" | "SB" if !args.is_empty() => self.text(&args.join(" ")),
+            "BR" | "RB" | "BI" | "IB" | "IR" | "RI" => self.text(&args.concat()),
+            "UR" | "MT" => self.url = arg(0).map(render_escapes),
+            "UE" | "ME" => {
+                if let Some(url) = self.url.take() {
+                    self.text(&format!("<{}>{}", url, arg(0).unwrap_or_default()));
+                }
+            }
+            "SY" => {
+                self.flush();
+                self.text(&args.join(" "));
+            }
+            "OP" => self.text(&format!("[{}]", args.join(" "))),
+            "YS" => self.flush(),
+            _ => {
+                if let Some(body) = self.macros.get(name).cloned() {
+                    self.expand_macro(&body, &args);
+                }
+                // Everything else only changes how the page looks in a typesetter
+            }
+        }
+    }
+
+    // Run the lines of a macro defined by the page, with \\$1 and so on replaced by its
+    // arguments
+    fn expand_macro(&mut self, body: &[String], args: &[String]) {
+        if self.macro_depth >= MAX_MACRO_DEPTH {
+            return;
+        }
+
+        self.macro_depth += 1;
+        for line in body {
+            let mut line = line.replace("\\\\", "\\");
+            for i in (1..=9).rev() {
+                let arg = args.get(i - 1).map(String::as_str).unwrap_or_default();
+                line = line.replace(&format!("\\${}", i), arg);
+            }
+            line = line.replace("\\$*", &args.join(" "));
+            self.line(&line);
+        }
+        self.macro_depth -= 1;
+    }
+
+    // Handle .if and .ie, only the conditions for terminals are known to be true
+    fn condition(&mut self, rest: &str, is_ie: bool) {
+        let rest = rest.trim_start();
+        let (condition, body) = rest
+            .split_once(|c: char| c.is_whitespace())
+            .unwrap_or((rest, ""));
+
+        let (negated, condition) = match condition.strip_prefix('!') {
+            Some(condition) => (true, condition),
+            None => (false, condition),
+        };
+        let result = (condition == "n") != negated;
+
+        if is_ie {
+            self.last_condition = result;
+        }
+        self.conditional_body(body.trim_start(), result);
+    }
+
+    fn conditional_body(&mut self, body: &str, condition: bool) {
+        let (body, opens_block) = match body.strip_prefix("\\{") {
+            Some(body) => (body.trim_start(), true),
+            None => (body, false),
+        };
+        let closes_block = body.contains("\\}");
+
+        if !condition {
+            if opens_block && !closes_block {
+                self.skip_depth = 1;
+            }
+            return;
+        }
+
+        let body = body.replace("\\}", "");
+        if !body.trim().is_empty() {
+            self.line(&body);
+        }
+    }
+
+    fn text(&mut self, line: &str) {
+        let joins = line.ends_with("\\c") && !line.ends_with("\\\\c");
+        let text = render_escapes(line);
+
+        if let Some(indent) = self.expect_heading.take() {
+            self.heading(&text, indent);
+            return;
+        }
+
+        if self.expect_tag {
+            self.expect_tag = false;
+            self.tag = Some(text.trim().to_string());
+            return;
+        }
+
+        if self.no_fill {
+            self.flush();
+            let indent = self.base_indent() + self.paragraph_indent;
+            let line = text.replace(UNBREAKABLE_SPACE, " ");
+            if line.trim().is_empty() {
+                self.push_blank_line();
+            } else {
+                self.push_line(&format!("{}{}", " ".repeat(indent), line.trim_end()));
+            }
+            return;
+        }
+
+        if text.trim().is_empty() {
+            self.flush();
+            self.blank_line();
+            return;
+        }
+
+        // A line starting with a space starts a new line in the output too
+        if text.starts_with(' ') {
+            self.flush();
+        }
+
+        let mut words = text.split_whitespace().map(str::to_string);
+        if self.join_next {
+            if let (Some(last), Some(first)) = (self.words.last_mut(), words.next()) {
+                last.push_str(&first);
+            }
+        }
+        self.words.extend(words);
+        self.join_next = joins;
+    }
+
+    fn heading(&mut self, text: &str, indent: usize) {
+        self.flush();
+        if !self.output.is_empty() {
+            self.blank_line();
+        }
+        self.push_line(&format!(
+            "{}{}",
+            " ".repeat(indent),
+            text.trim().replace(UNBREAKABLE_SPACE, " ")
+        ));
+        self.no_space = true;
+    }
+
+    // Lay out the words of the current paragraph
+    fn flush(&mut self) {
+        self.join_next = false;
+        let tag = self.tag.take();
+        if self.words.is_empty() && tag.is_none() {
+            return;
+        }
+
+        let base = self.base_indent();
+        let indent = base + self.paragraph_indent;
+        let mut line = String::new();
+
+        if let Some(tag) = tag {
+            let tag = tag.replace(UNBREAKABLE_SPACE, " ");
+            let tag_width = tag.chars().count();
+            line = format!("{}{}", " ".repeat(base), tag);
+
+            // Short tags share their line with the text, like "-a     text"
+            if base + tag_width + 2 <= indent && !self.words.is_empty() {
+                line.push_str(&" ".repeat(indent - base - tag_width));
+            } else {
+                self.push_line(&line);
+                line.clear();
+            }
+        }
+
+        let mut line_width = line.chars().count();
+        for word in std::mem::take(&mut self.words) {
+            let word = word.replace(UNBREAKABLE_SPACE, " ");
+            let word_width = word.chars().count();
+
+            if line.is_empty() {
+                line = " ".repeat(indent);
+                line_width = indent;
+            } else if line_width > indent && line_width + 1 + word_width > WIDTH {
+                self.push_line(&line);
+                line = " ".repeat(indent);
+                line_width = indent;
+            } else if line_width > indent {
+                line.push(' ');
+                line_width += 1;
+            }
+
+            line.push_str(&word);
+            line_width += word_width;
+        }
+
+        if !line.trim().is_empty() {
+            self.push_line(&line);
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        self.output.push_str(line);
+        self.output.push('\n');
+        self.no_space = false;
+    }
+
+    // Separate paragraphs by a single blank line
+    fn blank_line(&mut self) {
+        if !self.output.ends_with("\n\n") {
+            self.push_blank_line();
+        }
+    }
+
+    fn push_blank_line(&mut self) {
+        if !self.output.is_empty() && !self.no_space {
+            self.output.push('\n');
+        }
+    }
+
+    fn reset_indent(&mut self) {
+        self.flush();
+        self.base_indent = 0;
+        self.indent_stack.clear();
+        self.paragraph_indent = 0;
+        self.tag_width = DEFAULT_TAG_WIDTH;
+        self.no_fill = false;
+    }
+
+    // Text below a heading is indented, .RS indents on top of that
+    fn base_indent(&self) -> usize {
+        BASE_INDENT + self.base_indent
+    }
+
+    fn tag_width(&self) -> usize {
+        if self.tag_width == 0 {
+            DEFAULT_TAG_WIDTH
+        } else {
+            self.tag_width
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        let mut output = self.output.trim_end().to_string() + "\n";
+        if let Some(footer) = self.footer {
+            output.push('\n');
+            output.push_str(&footer);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+// Split the arguments of a request, double quotes group words and "" is a literal quote
+fn split_args(rest: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut chars = rest.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(first) = chars.next() else {
+            break;
+        };
+        if first == '\\' && chars.peek() == Some(&'"') {
+            break;
+        }
+
+        let mut arg = String::new();
+        let quoted = first == '"';
+        if !quoted {
+            arg.push(first);
+            if first == '\\' {
+                arg.extend(chars.next());
+            }
+        }
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    // A comment ends the arguments
+                    if chars.peek() == Some(&'"') {
+                        return finish_args(args, arg);
+                    }
+                    arg.push(c);
+                    arg.extend(chars.next());
+                }
+                '"' if quoted => {
+                    if chars.next_if_eq(&'"').is_some() {
+                        arg.push('"');
+                    } else {
+                        break;
+                    }
+                }
+                c if c.is_whitespace() && !quoted => break,
+                c => arg.push(c),
+            }
+        }
+        args.push(arg);
+    }
+
+    args
+}
+
+fn finish_args(mut args: Vec<String>, arg: String) -> Vec<String> {
+    if !arg.is_empty() {
+        args.push(arg);
+    }
+    args
+}
+
+// Parse widths like "7", "4n" or "0.5i" into columns
+fn parse_width(width: &str) -> Option<usize> {
+    let number: String = width
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let value: f64 = number.parse().ok()?;
+    let columns = match width[number.len()..].chars().next() {
+        Some('i') => value * 10.0,
+        Some('c') => value * 4.0,
+        Some('m') | Some('n') | None => value,
+        _ => return None,
+    };
+    Some(columns.round() as usize)
+}
+
+// Replace the escapes in a line of text, fonts and sizes are dropped
+fn render_escapes(text: &str) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            rendered.push(c);
+            continue;
+        }
+
+        let Some(escape) = chars.next() else {
+            break;
+        };
+        match escape {
+            // Comments run to the end of the line
+            '"' | '#' => break,
+            'f' | 'F' | 'n' | 'g' | 'k' | 'm' | 'M' | 'V' | 'Y' | '$' => {
+                chars.next_if(|c| matches!(c, '+' | '-'));
+                read_name(&mut chars);
+            }
+            's' => {
+                chars.next_if(|c| matches!(c, '+' | '-'));
+                match chars.peek() {
+                    Some('(' | '[' | '\'') => {
+                        read_name(&mut chars);
+                    }
+                    _ => {
+                        if let Some(digit) = chars.next() {
+                            if ('1'..='3').contains(&digit) {
+                                chars.next_if(|c| c.is_ascii_digit());
+                            }
+                        }
+                    }
+                }
+            }
+            '(' | '[' => {
+                let name = if escape == '(' {
+                    chars.by_ref().take(2).collect()
+                } else {
+                    chars.by_ref().take_while(|c| *c != ']').collect::<String>()
+                };
+                rendered.push_str(&glyph(&name));
+            }
+            'C' => {
+                let name = read_delimited(&mut chars);
+                rendered.push_str(&glyph(&name));
+            }
+            'N' => {
+                let code = read_delimited(&mut chars);
+                rendered.extend(code.parse().ok().and_then(char::from_u32));
+            }
+            '*' => {
+                let name = read_name(&mut chars);
+                rendered.push_str(string_value(&name));
+            }
+            'h' | 'v' | 'w' | 'o' | 'X' | 'Z' | 'b' | 'l' | 'L' | 'D' | 'x' | 'R' | 'A' | 'B' => {
+                read_delimited(&mut chars);
+            }
+            'e' | 'E' | '\\' => rendered.push('\\'),
+            ' ' | '~' | '0' => rendered.push(UNBREAKABLE_SPACE),
+            't' => rendered.push('\t'),
+            '-' => rendered.push('-'),
+            '\'' => rendered.push('\''),
+            '`' => rendered.push('`'),
+            // Zero width characters, italic corrections, hyphenation points and line joins
+            '&' | '|' | '^' | ')' | '%' | ':' | ',' | '/' | 'c' | '{' | '}' | 'd' | 'u' | 'p'
+            | 'r' | 'a' | 'z' => {}
+            other => rendered.push(other),
+        }
+    }
+
+    rendered
+}
+
+// Read the name of an escape: one character, two after '(', or up to ']' after '['
+fn read_name(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    match chars.next() {
+        Some('(') => chars.by_ref().take(2).collect(),
+        Some('[') => chars.by_ref().take_while(|c| *c != ']').collect(),
+        Some(c) => c.to_string(),
+        None => String::new(),
+    }
+}
+
+// Read an argument between two copies of the character that follows the escape
+fn read_delimited(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    match chars.next() {
+        Some(delimiter) => chars.by_ref().take_while(|c| *c != delimiter).collect(),
+        None => String::new(),
+    }
+}
+
+// Special characters like \(em and \[bu]
+fn glyph(name: &str) -> String {
+    let glyph = match name {
+        "em" => "—",
+        "en" => "–",
+        "hy" | "mi" | "\\-" => "-",
+        "bu" => "•",
+        "lq" | "rq" | "dq" => "\"",
+        "oq" | "cq" | "aq" | "fm" => "'",
+        "ga" => "`",
+        "ti" | "ap" => "~",
+        "ha" | "a^" => "^",
+        "rs" => "\\",
+        "ba" | "or" | "bv" => "|",
+        "co" => "©",
+        "rg" => "®",
+        "tm" => "™",
+        "de" => "°",
+        "mu" => "×",
+        "di" => "÷",
+        "+-" => "±",
+        "<=" => "≤",
+        ">=" => "≥",
+        "!=" => "≠",
+        "->" => "→",
+        "<-" => "←",
+        "sc" => "§",
+        "ps" => "¶",
+        "lh" => "☜",
+        "rh" => "☞",
+        "Fo" => "«",
+        "Fc" => "»",
+        "at" => "@",
+        "sh" => "#",
+        "Do" => "$",
+        "sl" => "/",
+        "lB" => "[",
+        "rB" => "]",
+        "lC" => "{",
+        "rC" => "}",
+        "la" => "<",
+        "ra" => ">",
+        _ => {
+            // Unicode characters like \[u00E9]
+            return name
+                .strip_prefix('u')
+                .and_then(|code| u32::from_str_radix(code, 16).ok())
+                .and_then(char::from_u32)
+                .map(|c| c.to_string())
+                .unwrap_or_default();
+        }
+    };
+    glyph.to_string()
+}
+
+// Strings predefined by the man macros, other strings are left out
+fn string_value(name: &str) -> &'static str {
+    match name {
+        "R" => "®",
+        "Tm" => "™",
+        "lq" | "rq" | "q" => "\"",
+        "Aq" => "'",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_headings_tagged_paragraphs_and_footer() {
+        let page = "\
+.TH TOOL 1 2024-01-01 \"tool 1.2\"
+.SH NAME
+tool \\- do things
+.SH OPTIONS
+.TP
+.B \\-a
+Show all.
+.TP 4
+.BR \\-\\-long =N
+Set it.
+";
+        assert_eq!(
+            render(page),
+            "\
+NAME
+       tool - do things
+
+OPTIONS
+       -a     Show all.
+
+       --long=N
+           Set it.
+
+tool 1.2  2024-01-01  TOOL(1)
+"
+        );
+    }
+
+    #[test]
+    fn fills_paragraphs_to_the_page_width() {
+        let words = vec!["word"; 40].join(" ");
+        let rendered = render(&format!(".SH DESCRIPTION\n{}\n.PP\nnext\n", words));
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert!(lines.len() > 3);
+        assert!(lines[1..lines.len() - 2]
+            .iter()
+            .all(|line| line.starts_with("       word") && line.len() <= WIDTH));
+        assert_eq!(lines[lines.len() - 2..], ["", "       next"]);
+    }
+
+    #[test]
+    fn keeps_lines_in_no_fill_mode_and_indents_with_rs() {
+        let page = ".SH EXAMPLES\n.nf\ntool  -a\ntool -b\n.fi\n.RS 4\nindented\n.RE\nback\n";
+        assert_eq!(
+            render(page),
+            "EXAMPLES\n       tool  -a\n       tool -b\n           indented\n       back\n"
+        );
+    }
+
+    #[test]
+    fn expands_macros_and_terminal_conditions() {
+        let page = "\
+.de Op
+[\\\\$1]
+..
+.SH SYNOPSIS
+tool
+.Op \\-v
+.ie n on a terminal
+.el in a typesetter
+.if t \\{\\
+typeset only
+.\\}
+";
+        assert_eq!(render(page), "SYNOPSIS\n       tool [-v] on a terminal\n");
+    }
+
+    #[test]
+    fn replaces_escapes() {
+        assert_eq!(
+            render_escapes(r#"\fBbold\fR \(em \[bu] \*(lqq\*(rq \e \[u00E9] a\ b \" comment"#),
+            "bold — • \"q\" \\ é a\u{a0}b "
+        );
+        assert_eq!(
+            render_escapes(r"\s-1small\s0 \N'65' \h'2n'x\c"),
+            "small A x"
+        );
+    }
+
+    #[test]
+    fn splits_request_arguments() {
+        assert_eq!(
+            split_args(r#" "a b"  c "say ""hi""" \" comment"#),
+            ["a b", "c", "say \"hi\""]
+        );
+        assert_eq!(split_args(r"\-a \fBx\fR"), [r"\-a", r"\fBx\fR"]);
+    }
+
+    #[test]
+    fn parses_widths() {
+        assert_eq!(parse_width("7"), Some(7));
+        assert_eq!(parse_width("4n"), Some(4));
+        assert_eq!(parse_width("0.5i"), Some(5));
+        assert_eq!(parse_width("x"), None);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::list::{enter_full_screen, leave_full_screen};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+};
+use std::io::{self, IsTerminal, Write};
+
+const TAB_WIDTH: usize = 8;
+
+// What the status line at the bottom is used for
+enum Prompt {
+    Position,
+    Search(String),
+    Message(String),
+}
+
+struct Pager<'a> {
+    title: &'a str,
+    lines: Vec<Vec<char>>,
+    top: usize,
+    // The last search, lowercased since searches ignore case
+    query: Vec<char>,
+    // Line of the match n and N continue from, it can be below the top near the end
+    found: Option<usize>,
+    prompt: Prompt,
+}
+
+// Show a text in a full-screen viewer, or print it when stdout is not a terminal
+pub fn show_text(title: &str, text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    if !stdout.is_terminal() {
+        return write!(stdout, "{}", text);
+    }
+
+    let mut pager = Pager {
+        title,
+        lines: text.lines().map(expand_tabs).collect(),
+        top: 0,
+        query: Vec::new(),
+        found: None,
+        prompt: Prompt::Position,
+    };
+
+    enter_full_screen(&mut stdout)?;
+    let result = pager.run(&mut stdout);
+    // The terminal is restored even when drawing failed
+    leave_full_screen(&mut stdout)?;
+    result
+}
+
+impl Pager<'_> {
+    fn run(&mut self, stdout: &mut io::Stdout) -> io::Result<()> {
+        loop {
+            let (width, height) = terminal::size()?;
+            // The last row is the status line
+            let page = (height as usize).saturating_sub(1).max(1);
+            self.top = self.top.min(self.lines.len().saturating_sub(page));
+            self.draw(stdout, width as usize, page)?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+
+            if let Prompt::Search(query) = &mut self.prompt {
+                match key.code {
+                    KeyCode::Enter => {
+                        self.query = query.to_lowercase().chars().collect();
+                        self.prompt = Prompt::Position;
+                        self.find_next(self.top, page);
+                    }
+                    KeyCode::Esc => self.prompt = Prompt::Position,
+                    KeyCode::Backspace if query.is_empty() => self.prompt = Prompt::Position,
+                    KeyCode::Backspace => {
+                        query.pop();
+                    }
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.prompt = Prompt::Position;
+                    }
+                    KeyCode::Char(c) => query.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
+            self.prompt = Prompt::Position;
+            let last_top = self.lines.len().saturating_sub(page);
+            let top = self.top;
+            match key {
+                KeyEvent {
+                    code: KeyCode::Char('q') | KeyCode::Esc,
+                    ..
+                } => break,
+                KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                } => break,
+                KeyEvent {
+                    code: KeyCode::Down | KeyCode::Char('j') | KeyCode::Enter,
+                    ..
+                } => self.top = (self.top + 1).min(last_top),
+                KeyEvent {
+                    code: KeyCode::Up | KeyCode::Char('k'),
+                    ..
+                } => self.top = self.top.saturating_sub(1),
+                KeyEvent {
+                    code: KeyCode::PageDown | KeyCode::Char(' ') | KeyCode::Char('f'),
+                    ..
+                } => self.top = (self.top + page).min(last_top),
+                KeyEvent {
+                    code: KeyCode::PageUp | KeyCode::Char('b'),
+                    ..
+                } => self.top = self.top.saturating_sub(page),
+                KeyEvent {
+                    code: KeyCode::Home | KeyCode::Char('g'),
+                    ..
+                } => self.top = 0,
+                KeyEvent {
+                    code: KeyCode::End | KeyCode::Char('G'),
+                    ..
+                } => self.top = last_top,
+                KeyEvent {
+                    code: KeyCode::Char('/'),
+                    ..
+                } => self.prompt = Prompt::Search(String::new()),
+                KeyEvent {
+                    code: KeyCode::Char('n'),
+                    ..
+                } => self.find_next(self.found.map_or(self.top, |line| line + 1), page),
+                KeyEvent {
+                    code: KeyCode::Char('N'),
+                    ..
+                } => self.find_previous(self.found.unwrap_or(self.top), page),
+                _ => {}
+            }
+
+            // Scrolling by hand makes searches continue from the new position
+            let searched = matches!(key.code, KeyCode::Char('n') | KeyCode::Char('N'));
+            if self.top != top && !searched {
+                self.found = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, stdout: &mut io::Stdout, width: usize, page: usize) -> io::Result<()> {
+        for row in 0..page {
+            queue!(
+                stdout,
+                cursor::MoveTo(0, row as u16),
+                terminal::Clear(ClearType::CurrentLine)
+            )?;
+            if let Some(line) = self.lines.get(self.top + row) {
+                self.draw_line(stdout, &line[..line.len().min(width)])?;
+            }
+        }
+
+        let status = match &self.prompt {
+            Prompt::Position => format!(
+                "{}  lines {}-{} of {}  (q to quit, / to search)",
+                self.title,
+                (self.top + 1).min(self.lines.len()),
+                (self.top + page).min(self.lines.len()),
+                self.lines.len()
+            ),
+            Prompt::Search(query) => format!("/{}", query),
+            Prompt::Message(message) => message.clone(),
+        };
+        let status = status.chars().take(width).collect::<String>();
+        queue!(
+            stdout,
+            cursor::MoveTo(0, page as u16),
+            terminal::Clear(ClearType::CurrentLine),
+            SetAttribute(Attribute::Reverse),
+            Print(status),
+            SetAttribute(Attribute::Reset)
+        )?;
+        stdout.flush()
+    }
+
+    // Matches of the last search are shown in reverse video
+    fn draw_line(&self, stdout: &mut io::Stdout, line: &[char]) -> io::Result<()> {
+        let mut start = 0;
+        for (from, to) in find_matches(line, &self.query) {
+            queue!(
+                stdout,
+                Print(line[start..from].iter().collect::<String>()),
+                SetAttribute(Attribute::Reverse),
+                Print(line[from..to].iter().collect::<String>()),
+                SetAttribute(Attribute::Reset)
+            )?;
+            start = to;
+        }
+        queue!(stdout, Print(line[start..].iter().collect::<String>()))
+    }
+
+    // Scroll to the first line from `from` on that matches, wrapping around to the start
+    fn find_next(&mut self, from: usize, page: usize) {
+        let count = self.lines.len();
+        let found = (0..count)
+            .map(|offset| (from + offset) % count.max(1))
+            .find(|&index| self.line_matches(index));
+        self.scroll_to(found, page);
+    }
+
+    // Scroll to the last line before `before` that matches, wrapping around to the end
+    fn find_previous(&mut self, before: usize, page: usize) {
+        let count = self.lines.len();
+        let found = (1..=count)
+            .map(|offset| (before + count - offset) % count.max(1))
+            .find(|&index| self.line_matches(index));
+        self.scroll_to(found, page);
+    }
+
+    fn scroll_to(&mut self, found: Option<usize>, page: usize) {
+        if self.query.is_empty() {
+            return;
+        }
+        match found {
+            Some(index) => {
+                self.found = Some(index);
+                self.top = index.min(self.lines.len().saturating_sub(page));
+            }
+            None => {
+                self.prompt = Prompt::Message(format!(
+                    "Pattern not found: {}",
+                    self.query.iter().collect::<String>()
+                ))
+            }
+        }
+    }
+
+    fn line_matches(&self, index: usize) -> bool {
+        !find_matches(&self.lines[index], &self.query).is_empty()
+    }
+}
+
+// Find the ranges of a lowercased query in a line, ignoring case
+fn find_matches(line: &[char], query: &[char]) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return matches;
+    }
+
+    let mut start = 0;
+    while start + query.len() <= line.len() {
+        let found = line[start..start + query.len()]
+            .iter()
+            .zip(query)
+            .all(|(c, q)| c.to_lowercase().eq(std::iter::once(*q)));
+        if found {
+            matches.push((start, start + query.len()));
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+
+    matches
+}
+
+// Tabs are expanded so every char takes one column
+fn expand_tabs(line: &str) -> Vec<char> {
+    let mut expanded = Vec::with_capacity(line.len());
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = TAB_WIDTH - expanded.len() % TAB_WIDTH;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+        } else {
+            expanded.push(c);
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    fn pager(text: &str, query: &str) -> Pager<'static> {
+        Pager {
+            title: "test",
+            lines: text.lines().map(expand_tabs).collect(),
+            top: 0,
+            query: chars(query),
+            found: None,
+            prompt: Prompt::Position,
+        }
+    }
+
+    #[test]
+    fn finds_matches_ignoring_case() {
+        assert_eq!(
+            find_matches(&chars("-a, --All  show ALL"), &chars("all")),
+            [(6, 9), (16, 19)]
+        );
+        assert!(find_matches(&chars("text"), &[]).is_empty());
+    }
+
+    #[test]
+    fn expands_tabs_to_the_next_stop() {
+        assert_eq!(expand_tabs("\tx"), chars("        x"));
+        assert_eq!(expand_tabs("ab\tx"), chars("ab      x"));
+    }
+
+    #[test]
+    fn searches_wrap_around_the_text() {
+        let mut pager = pager("one\nmatch\ntwo\nthree\nmatch\n", "match");
+
+        pager.find_next(2, 2);
+        assert_eq!((pager.found, pager.top), (Some(4), 3));
+        pager.find_next(5, 2);
+        assert_eq!((pager.found, pager.top), (Some(1), 1));
+        pager.find_previous(1, 2);
+        assert_eq!(pager.found, Some(4));
+    }
+
+    #[test]
+    fn reports_missing_patterns() {
+        let mut pager = pager("one\ntwo\n", "three");
+
+        pager.find_next(0, 2);
+        assert_eq!(pager.found, None);
+        assert!(matches!(&pager.prompt, Prompt::Message(m) if m == "Pattern not found: three"));
+    }
+}
@@ -0,0 +1,124 @@
+use std::ops::RangeInclusive;
+
+// Paragraphs are cut off after this many lines on either side of the match
+const MAX_PARAGRAPH_LINES: usize = 12;
+
+#[derive(Debug, Default)]
+pub enum ContextMode {
+    Lines {
+        before: usize,
+        after: usize,
+    },
+    #[default]
+    Paragraph,
+}
+
+// Get the range of line indices to show around the matched line at `index`
+pub fn context_range(lines: &[&str], index: usize, mode: &ContextMode) -> RangeInclusive<usize> {
+    match mode {
+        ContextMode::Lines { before, after } => {
+            index.saturating_sub(*before)..=(index + after).min(lines.len() - 1)
+        }
+        ContextMode::Paragraph => paragraph_range(lines, index),
+    }
+}
+
+// Use the indentation of the help text to find the option block around a line: the line
+// that starts the block (usually a flag) and the more deeply indented lines wrapping below it
+fn paragraph_range(lines: &[&str], index: usize) -> RangeInclusive<usize> {
+    let min_head = index.saturating_sub(MAX_PARAGRAPH_LINES);
+    let max_tail = (index + MAX_PARAGRAPH_LINES).min(lines.len() - 1);
+
+    // Walk up through the wrapped lines of the same block
+    let mut head = index;
+    let base = indent(lines[index]);
+    while head > min_head
+        && !is_flag_line(lines[head])
+        && !is_blank(lines[head - 1])
+        && indent(lines[head - 1]) >= base
+    {
+        head -= 1;
+    }
+
+    // A less indented line directly above is the line that starts the block
+    if head > min_head
+        && !is_flag_line(lines[head])
+        && !is_blank(lines[head - 1])
+        && indent(lines[head - 1]) < base
+    {
+        head -= 1;
+    }
+
+    // Walk down until the next block starts
+    let head_indent = indent(lines[head]);
+    let head_is_flag = is_flag_line(lines[head]);
+    let mut tail = head;
+    while tail < max_tail {
+        let next = lines[tail + 1];
+        let continues = if head_is_flag {
+            indent(next) > head_indent
+        } else {
+            indent(next) >= head_indent
+        };
+
+        if is_blank(next) || is_flag_line(next) || !continues {
+            break;
+        }
+        tail += 1;
+    }
+
+    head..=tail.max(index)
+}
+
+fn indent(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn is_flag_line(line: &str) -> bool {
+    line.trim_start().starts_with('-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELP: &str = "\
+Usage: tool [OPTION]...
+
+Options:
+  -a, --all          show entries starting with .
+                       and the implied . and ..
+  -l                 use a long listing format
+  --color[=WHEN]     color the output WHEN defaults to always
+      or can be never or auto
+";
+
+    fn range(index: usize, mode: &ContextMode) -> RangeInclusive<usize> {
+        let lines = HELP.lines().collect::<Vec<&str>>();
+        context_range(&lines, index, mode)
+    }
+
+    #[test]
+    fn shows_lines_around_the_match_within_the_text() {
+        let mode = ContextMode::Lines {
+            before: 1,
+            after: 2,
+        };
+        assert_eq!(range(3, &mode), 2..=5);
+        assert_eq!(range(0, &mode), 0..=2);
+        assert_eq!(range(7, &mode), 6..=7);
+    }
+
+    #[test]
+    fn shows_the_option_block_around_the_match() {
+        let mode = ContextMode::Paragraph;
+        assert_eq!(range(3, &mode), 3..=4);
+        assert_eq!(range(4, &mode), 3..=4);
+        assert_eq!(range(5, &mode), 5..=5);
+        assert_eq!(range(7, &mode), 6..=7);
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SearchError {
+    DatabaseError(rusqlite::Error),
+    PatternError(glob::PatternError),
+    UnknownProgram(String, Vec<String>),
+}
+
+// Implement the Display trait for the SearchError enum
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::DatabaseError(err) => write!(f, "{}", err),
+            SearchError::PatternError(err) => write!(f, "Invalid program pattern: {}", err),
+            SearchError::UnknownProgram(name, suggestions) => {
+                write!(f, "No program matches \"{}\"", name)?;
+                if !suggestions.is_empty() {
+                    write!(f, ", did you mean: {}?", suggestions.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SearchError::DatabaseError(err) => err.source(),
+            SearchError::PatternError(err) => err.source(),
+            SearchError::UnknownProgram(..) => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for SearchError {
+    fn from(err: rusqlite::Error) -> SearchError {
+        SearchError::DatabaseError(err)
+    }
+}
+
+impl From<glob::PatternError> for SearchError {
+    fn from(err: glob::PatternError) -> SearchError {
+        SearchError::PatternError(err)
+    }
+}
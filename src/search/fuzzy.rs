@@ -0,0 +1,97 @@
+use strsim::normalized_levenshtein;
+
+// Lines scoring below this are not considered a match
+const FUZZY_THRESHOLD: f64 = 0.75;
+
+// Score how well a line matches the query, exact hits score 1.0 and near misses score below that
+pub fn fuzzy_line_score(line: &str, query: &str, terms: &[&str]) -> Option<f64> {
+    let line = line.to_lowercase();
+
+    if line.contains(query) {
+        return Some(1.0);
+    }
+
+    let tokens = line
+        .split(|c: char| c.is_whitespace() || ",=[]<>()|".contains(c))
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<&str>>();
+
+    // Every term has to come close to some word on the line, the worst term decides the score
+    let mut score: f64 = 1.0;
+    for term in terms {
+        let best = tokens
+            .iter()
+            .map(|token| term_score(term, token))
+            .fold(0.0, f64::max);
+        score = score.min(best);
+    }
+
+    if terms.is_empty() || score < FUZZY_THRESHOLD {
+        return None;
+    }
+
+    // Keep near misses below exact hits
+    Some(score.min(0.99))
+}
+
+fn term_score(term: &str, token: &str) -> f64 {
+    // Only compare the dashes of a flag when the term itself is written as a flag
+    let token = if term.starts_with('-') {
+        token
+    } else {
+        token.trim_start_matches('-')
+    };
+
+    if token.is_empty() {
+        return 0.0;
+    }
+
+    let distance = normalized_levenshtein(term, token);
+    let subsequence = if is_subsequence(term, token) {
+        term.chars().count() as f64 / token.chars().count() as f64
+    } else {
+        0.0
+    };
+
+    distance.max(subsequence)
+}
+
+fn is_subsequence(term: &str, token: &str) -> bool {
+    let mut token_chars = token.chars();
+    term.chars().all(|c| token_chars.any(|t| t == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(line: &str, query: &str) -> Option<f64> {
+        let terms = query.split_whitespace().collect::<Vec<&str>>();
+        fuzzy_line_score(line, query, &terms)
+    }
+
+    #[test]
+    fn scores_exact_hits_highest() {
+        assert_eq!(score("  -n, --dry-run  Only print", "--dry-run"), Some(1.0));
+    }
+
+    #[test]
+    fn matches_near_misses_below_exact_hits() {
+        let near = score("  -n, --dry-run  Only print", "--dry-rn").unwrap();
+        assert!((FUZZY_THRESHOLD..1.0).contains(&near));
+        assert!(score("  --recursive  Copy directories", "recursve").is_some());
+        assert!(score("  --verbose  Print more", "verbos").is_some());
+    }
+
+    #[test]
+    fn skips_unrelated_lines() {
+        assert_eq!(score("  -n, --dry-run  Only print", "compress"), None);
+        assert_eq!(score("  --verbose  Print more", "--dry-rn"), None);
+    }
+
+    #[test]
+    fn needs_every_term_to_come_close() {
+        assert!(score("  --force  Overwrite files", "forse overwrit").is_some());
+        assert_eq!(score("  --force  Overwrite files", "forse compress"), None);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::search::{query_terms, search_options::SearchOptions};
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+pub struct Matcher {
+    regex: Regex,
+    word: bool,
+}
+
+impl Matcher {
+    // Build the matcher for a query, plain queries match any of their terms literally, like the
+    // search index does
+    pub fn new(query: &str, options: &SearchOptions) -> Result<Self, regex::Error> {
+        let pattern = if options.regex {
+            query.to_string()
+        } else {
+            let mut terms = query_terms(query);
+            if terms.is_empty() {
+                terms.push(query.trim());
+            }
+
+            // Prefer the longest term where terms overlap
+            terms.sort_by_key(|t| std::cmp::Reverse(t.len()));
+            terms
+                .iter()
+                .map(|t| regex::escape(t))
+                .collect::<Vec<String>>()
+                .join("|")
+        };
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()?;
+
+        Ok(Matcher {
+            regex,
+            word: options.word,
+        })
+    }
+
+    // Find the byte ranges of all matches in a line
+    pub fn find_all(&self, line: &str) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+
+        while let Some(m) = self.regex.find_at(line, start) {
+            if !m.is_empty() && (!self.word || is_whole_word(line, m.range())) {
+                ranges.push(m.range());
+                start = m.end();
+            } else {
+                // Retry from the next character so a later occurrence can still match
+                match line[m.start()..].chars().next() {
+                    Some(c) => start = m.start() + c.len_utf8(),
+                    None => break,
+                }
+            }
+        }
+
+        ranges
+    }
+}
+
+// Like grep -w, a match has to be surrounded by characters that are not part of a word
+fn is_whole_word(line: &str, range: Range<usize>) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    let before = line[..range.start].chars().next_back();
+    let after = line[range.end..].chars().next();
+
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The start and text of every match
+    fn find<'a>(query: &str, options: SearchOptions, line: &'a str) -> Vec<(usize, &'a str)> {
+        Matcher::new(query, &options)
+            .unwrap()
+            .find_all(line)
+            .into_iter()
+            .map(|range| (range.start, &line[range]))
+            .collect()
+    }
+
+    fn word() -> SearchOptions {
+        SearchOptions {
+            word: true,
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn finds_every_term_ignoring_case() {
+        let line = "  -R, --recursive  Recurse into directories";
+        assert_eq!(
+            find("recurs", SearchOptions::default(), line),
+            [(8, "recurs"), (19, "Recurs")]
+        );
+        assert_eq!(
+            find("dir into", SearchOptions::default(), line),
+            [(27, "into"), (32, "dir")]
+        );
+    }
+
+    #[test]
+    fn matches_case_and_regex_when_asked() {
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(find("R", options, "  -r, -R"), [(7, "R")]);
+
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(
+            find(r"--\w+=", options, "  --color=WHEN"),
+            [(2, "--color=")]
+        );
+    }
+
+    #[test]
+    fn whole_words_keep_short_flags_apart_from_long_ones() {
+        assert_eq!(find("-a", word(), "  -a, --all"), [(2, "-a")]);
+        assert!(find("-a", word(), "  --all").is_empty());
+        assert_eq!(find("all", word(), "  --all"), [(4, "all")]);
+    }
+
+    #[test]
+    fn whole_words_do_not_match_inside_longer_words() {
+        assert_eq!(find("log", word(), "git log"), [(4, "log")]);
+        assert!(find("log", word(), "  --login").is_empty());
+        assert!(find("log", word(), "  --log_level").is_empty());
+        assert_eq!(find("log", word(), "login log"), [(6, "log")]);
+    }
+}
@@ -0,0 +1,301 @@
+pub mod context;
+pub mod errors;
+pub mod fuzzy;
+pub mod matcher;
+pub mod output;
+pub mod program_filter;
+pub mod search_options;
+pub mod search_result;
+
+use crate::db::{
+    program::Program, program_option::ProgramOption, search_index::search_index, section::Section,
+};
+use context::context_range;
+use fuzzy::fuzzy_line_score;
+use matcher::Matcher;
+use rusqlite::{Connection, Result};
+use search_options::SearchOptions;
+use search_result::{LineMatch, OptionResult, SearchResult};
+use std::collections::HashMap;
+
+// Split a query into the words the search index can look up
+pub fn query_terms(query: &str) -> Vec<&str> {
+    query
+        .split_whitespace()
+        .filter(|t| t.chars().any(|c| c.is_alphanumeric()))
+        .collect::<Vec<&str>>()
+}
+
+// Search the help texts of all programs for the query, best ranked program first
+pub fn search_programs(
+    conn: &Connection,
+    query: &str,
+    matcher: &Matcher,
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    let query = query.to_lowercase();
+    let terms = query_terms(&query);
+
+    // The index only knows about whole words and their prefixes, so it only ranks the
+    // programs, every program is scanned for the query so substrings like "ort" in "sort"
+    // are found too. Fuzzy and regex queries and queries made up of punctuation are not ranked
+    let scores = if options.fuzzy || options.regex || terms.is_empty() {
+        HashMap::new()
+    } else {
+        search_index(conn, &terms)?
+            .into_iter()
+            .map(|hit| (hit.program_id, hit.score))
+            .collect::<HashMap<i32, f64>>()
+    };
+
+    let candidates = Program::get_all(conn)?
+        .into_iter()
+        .filter(|program| match &options.program_ids {
+            Some(ids) => program.id.is_some_and(|id| ids.contains(&id)),
+            None => true,
+        })
+        .map(|program| {
+            let score = program.id.and_then(|id| scores.get(&id).copied());
+            (program, score.unwrap_or(0.0))
+        });
+
+    let mut results = Vec::new();
+
+    for (program, score) in candidates {
+        let mut result = SearchResult::new(program, score);
+        let mut matches = Vec::new();
+
+        let lines = result.program.help_text.lines().collect::<Vec<&str>>();
+        let sections = match result.program.id {
+            Some(id) => Section::get_by_program(conn, id)?,
+            None => Vec::new(),
+        };
+
+        for (index, line) in lines.iter().enumerate() {
+            let section = Section::find(&sections, index + 1);
+            if !in_sections(section, options) {
+                continue;
+            }
+
+            let ranges = matcher.find_all(line);
+
+            let line_score = if options.fuzzy {
+                fuzzy_line_score(line, &query, &terms)
+            } else if !ranges.is_empty() {
+                Some(1.0)
+            } else {
+                None
+            };
+
+            if let Some(line_score) = line_score {
+                let context = context_range(&lines, index, &options.context);
+                matches.push(LineMatch {
+                    line_number: index + 1,
+                    line: line.to_string(),
+                    ranges,
+                    score: line_score,
+                    context: context.start() + 1..=context.end() + 1,
+                    section: section.map(|s| s.name.clone()),
+                });
+            }
+        }
+
+        if matches.is_empty() {
+            continue;
+        }
+        result.lines = matches;
+
+        // Fuzzy results are ranked by their best line instead of by the index
+        if options.fuzzy {
+            result.lines.sort_by(|a, b| {
+                b.score
+                    .total_cmp(&a.score)
+                    .then(a.line_number.cmp(&b.line_number))
+            });
+            result.score = result.lines[0].score;
+        }
+
+        results.push(result);
+    }
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    Ok(results)
+}
+
+// Search the parsed options of all programs, queries written as a flag only match that flag
+pub fn search_program_options(
+    conn: &Connection,
+    query: &str,
+    matcher: &Matcher,
+    options: &SearchOptions,
+) -> Result<Vec<OptionResult>> {
+    let query = query.trim();
+    let exact_flag = query.starts_with('-') && !options.regex;
+
+    let mut results = Vec::new();
+
+    for program in Program::get_all(conn)? {
+        let Some(id) = program.id else {
+            continue;
+        };
+        if let Some(ids) = &options.program_ids {
+            if !ids.contains(&id) {
+                continue;
+            }
+        }
+
+        let sections = Section::get_by_program(conn, id)?;
+        let matches = ProgramOption::get_by_program(conn, id)?
+            .into_iter()
+            .filter(|o| in_sections(Section::find(&sections, o.line_number), options))
+            .filter(|o| {
+                if exact_flag {
+                    o.short_flag.as_deref() == Some(query) || o.long_flag.as_deref() == Some(query)
+                } else {
+                    !matcher.find_all(&o.flags()).is_empty()
+                        || !matcher.find_all(&o.description).is_empty()
+                }
+            })
+            .collect::<Vec<ProgramOption>>();
+
+        if !matches.is_empty() {
+            results.push(OptionResult {
+                program,
+                options: matches,
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+// Check a line against the sections the search is limited to
+fn in_sections(section: Option<&Section>, options: &SearchOptions) -> bool {
+    match &options.sections {
+        Some(kinds) => section.is_some_and(|s| kinds.contains(&s.kind)),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::help_command::HelpCommand;
+    use crate::db::{open_test_connection, section::SectionKind};
+
+    fn database(programs: &[(&str, &str)]) -> Connection {
+        let conn = open_test_connection();
+        for (name, help_text) in programs {
+            let command = format!("{} --help", name);
+            Program::new(
+                name,
+                vec![HelpCommand::parse(&command, false).unwrap()],
+                help_text,
+            )
+            .insert(&conn)
+            .unwrap();
+        }
+        conn
+    }
+
+    fn search(conn: &Connection, query: &str) -> Vec<SearchResult> {
+        let options = SearchOptions::default();
+        let matcher = Matcher::new(query, &options).unwrap();
+        search_programs(conn, query, &matcher, &options).unwrap()
+    }
+
+    #[test]
+    fn ranks_programs_matching_in_their_name_first() {
+        let conn = database(&[
+            ("zip", "Package files, unlike tar it compresses each file\n"),
+            ("tar", "Usage: tar [OPTION...]\nStore files in an archive\n"),
+            ("cat", "Usage: cat [FILE]...\n"),
+        ]);
+
+        let results = search(&conn, "tar");
+        let names: Vec<&str> = results.iter().map(|r| r.program.name.as_str()).collect();
+        assert_eq!(names, ["tar", "zip"]);
+        assert_eq!(results[0].lines[0].line, "Usage: tar [OPTION...]");
+    }
+
+    #[test]
+    fn scans_every_program_for_queries_without_words() {
+        let conn = database(&[
+            ("ls", "Usage: ls\n  -a, --all  do not ignore entries\n"),
+            ("cat", "Usage: cat\n"),
+        ]);
+
+        let results = search(&conn, "--");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].program.name, "ls");
+        assert_eq!(results[0].lines[0].line_number, 2);
+    }
+
+    #[test]
+    fn flag_queries_only_match_that_flag() {
+        let conn = database(&[(
+            "ls",
+            "Usage: ls\n  -a, --all      do not ignore entries\n  -A, --almost-all  like -a\n",
+        )]);
+        let options = SearchOptions::default();
+        let matcher = Matcher::new("-a", &options).unwrap();
+
+        let results = search_program_options(&conn, "-a", &matcher, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].options.len(), 1);
+        assert_eq!(results[0].options[0].long_flag.as_deref(), Some("--all"));
+    }
+
+    #[test]
+    fn limits_matches_to_the_requested_sections() {
+        let conn = database(&[(
+            "tar",
+            "NAME\n       tar - archive files\nOPTIONS\n       -f, --file=ARCHIVE  use archive file\n",
+        )]);
+        let options = SearchOptions {
+            sections: Some(vec![SectionKind::Options]),
+            ..SearchOptions::default()
+        };
+        let matcher = Matcher::new("archive", &options).unwrap();
+
+        let results = search_programs(&conn, "archive", &matcher, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].lines.len(), 1);
+        assert_eq!(results[0].lines[0].line_number, 4);
+        assert_eq!(results[0].lines[0].section.as_deref(), Some("OPTIONS"));
+    }
+
+    #[test]
+    fn matches_any_term_and_ranks_programs_matching_more_first() {
+        let conn = database(&[
+            ("zip", "Usage: zip\n  -r  travel the directory structure\n"),
+            (
+                "tar",
+                "Usage: tar\n  -C  change to directory\n  -z  compress the archive\n",
+            ),
+            ("cat", "Usage: cat\n"),
+        ]);
+
+        let results = search(&conn, "directory compress");
+        let names: Vec<&str> = results.iter().map(|r| r.program.name.as_str()).collect();
+        assert_eq!(names, ["tar", "zip"]);
+        assert_eq!(results[0].lines.len(), 2);
+    }
+
+    #[test]
+    fn finds_substrings_of_words() {
+        let conn = database(&[
+            (
+                "ls",
+                "Usage: ls\n  --sort=WORD  sort by WORD instead of name\n",
+            ),
+            ("cat", "Usage: cat\n"),
+        ]);
+
+        let results = search(&conn, "ort");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].program.name, "ls");
+        assert_eq!(results[0].lines[0].ranges, [5..8, 16..19]);
+    }
+}
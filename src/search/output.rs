@@ -0,0 +1,328 @@
+use crate::search::{
+    context::ContextMode,
+    search_options::SearchOptions,
+    search_result::{LineMatch, OptionResult, SearchResult},
+};
+use colored::Colorize;
+use serde::Serialize;
+use std::io::{self, IsTerminal, Write};
+use std::ops::{Range, RangeInclusive};
+
+// Version of the JSON written for search results, bump it when a field changes meaning or is
+// removed, adding a field keeps the version
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn from_arg(s: &str) -> Self {
+        match s {
+            "always" => ColorChoice::Always,
+            "never" => ColorChoice::Never,
+            _ => ColorChoice::Auto,
+        }
+    }
+
+    // Turn colors on or off for everything printed after this
+    pub fn apply(&self) {
+        let enabled = match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        };
+        colored::control::set_override(enabled);
+    }
+}
+
+pub enum OutputFormat {
+    Text,
+    Json,
+    // One JSON object per line
+    JsonLines,
+}
+
+impl OutputFormat {
+    pub fn from_arg(s: &str) -> Self {
+        match s {
+            "json" => OutputFormat::Json,
+            "jsonl" => OutputFormat::JsonLines,
+            _ => OutputFormat::Text,
+        }
+    }
+}
+
+// A matched line as written in JSON, see JSON_SCHEMA_VERSION
+#[derive(Serialize)]
+struct JsonHit<'a> {
+    schema_version: u32,
+    program: &'a str,
+    program_id: Option<i32>,
+    line_number: usize,
+    line: &'a str,
+    // Byte offsets of the matches in the line, the end is exclusive
+    ranges: Vec<JsonRange>,
+    section: Option<&'a str>,
+    snippet: JsonSnippet,
+    score: f64,
+    program_score: f64,
+}
+
+#[derive(Serialize)]
+struct JsonRange {
+    start: usize,
+    end: usize,
+}
+
+// The lines shown around a match, the line numbers are inclusive
+#[derive(Serialize)]
+struct JsonSnippet {
+    start_line: usize,
+    end_line: usize,
+    text: String,
+}
+
+// Write the matched lines as a JSON array, or as JSON lines
+pub fn print_json_results(results: &[SearchResult], format: &OutputFormat) -> io::Result<()> {
+    let hits = json_hits(results);
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::JsonLines => {
+            for hit in &hits {
+                serde_json::to_writer(&mut stdout, hit)?;
+                writeln!(stdout)?;
+            }
+        }
+        _ => {
+            serde_json::to_writer_pretty(&mut stdout, &hits)?;
+            writeln!(stdout)?;
+        }
+    }
+    Ok(())
+}
+
+fn json_hits(results: &[SearchResult]) -> Vec<JsonHit<'_>> {
+    let mut hits = Vec::new();
+    for result in results {
+        let lines = result.program.help_text.lines().collect::<Vec<&str>>();
+        for m in &result.lines {
+            hits.push(JsonHit {
+                schema_version: JSON_SCHEMA_VERSION,
+                program: &result.program.name,
+                program_id: result.program.id,
+                line_number: m.line_number,
+                line: &m.line,
+                ranges: m
+                    .ranges
+                    .iter()
+                    .map(|r| JsonRange {
+                        start: r.start,
+                        end: r.end,
+                    })
+                    .collect(),
+                section: m.section.as_deref(),
+                snippet: JsonSnippet {
+                    start_line: *m.context.start(),
+                    end_line: *m.context.end(),
+                    text: lines[m.context.start() - 1..*m.context.end()].join("\n"),
+                },
+                score: m.score,
+                program_score: result.score,
+            });
+        }
+    }
+    hits
+}
+
+pub fn print_results(results: &[SearchResult], options: &SearchOptions) {
+    // Like grep, snippets are only separated when there is context around the matches
+    let separate = !matches!(
+        options.context,
+        ContextMode::Lines {
+            before: 0,
+            after: 0
+        }
+    );
+
+    for result in results {
+        // Results are in ranking order, BM25 scores say little on their own so they are not shown
+        println!("{}", result.program.name.bold());
+
+        let lines = result.program.help_text.lines().collect::<Vec<&str>>();
+        let snippets = merge_snippets(&result.lines);
+
+        for (index, snippet) in snippets.iter().enumerate() {
+            if index > 0 && separate {
+                println!("    {}", "--".dimmed());
+            }
+
+            for line_number in snippet.clone() {
+                match result.lines.iter().find(|m| m.line_number == line_number) {
+                    Some(m) => println!(
+                        "{} {}{}",
+                        format!("{:>6}:", line_number).dimmed(),
+                        section_label(m),
+                        highlight(&m.line, &m.ranges)
+                    ),
+                    None => println!(
+                        "{} {}",
+                        format!("{:>6}-", line_number).dimmed(),
+                        lines[line_number - 1]
+                    ),
+                }
+            }
+        }
+        println!();
+    }
+}
+
+// Print every matched option on one line, with the flags lined up
+pub fn print_option_results(results: &[OptionResult]) {
+    for result in results {
+        println!("{}", result.program.name.bold());
+
+        let width = result
+            .options
+            .iter()
+            .map(|o| o.flags().chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for option in &result.options {
+            println!(
+                "{} {:<width$}  {}",
+                format!("{:>6}:", option.line_number).dimmed(),
+                option.flags().yellow(),
+                option.description,
+                width = width
+            );
+        }
+        println!();
+    }
+}
+
+// Merge the snippets of matches that overlap or touch, keeping the order of the matches
+fn merge_snippets(matches: &[LineMatch]) -> Vec<RangeInclusive<usize>> {
+    let mut snippets: Vec<(usize, RangeInclusive<usize>)> = Vec::new();
+
+    for (order, m) in matches.iter().enumerate() {
+        let mut order = order;
+        let mut snippet = m.context.clone();
+
+        // Growing a snippet can make it touch others, so keep merging until nothing touches it
+        while let Some(index) = snippets
+            .iter()
+            .position(|(_, s)| *snippet.start() <= s.end() + 1 && snippet.end() + 1 >= *s.start())
+        {
+            let (other_order, other) = snippets.remove(index);
+            order = order.min(other_order);
+            snippet = *snippet.start().min(other.start())..=*snippet.end().max(other.end());
+        }
+
+        snippets.push((order, snippet));
+    }
+
+    snippets.sort_by_key(|(order, _)| *order);
+    snippets.into_iter().map(|(_, snippet)| snippet).collect()
+}
+
+// Label a matched line with the heading of its section, e.g. "[OPTIONS] "
+fn section_label(m: &LineMatch) -> String {
+    match &m.section {
+        Some(section) => format!("{} ", format!("[{}]", section).cyan()),
+        None => String::new(),
+    }
+}
+
+// Highlight the matched byte ranges of a line
+fn highlight(line: &str, ranges: &[Range<usize>]) -> String {
+    let mut highlighted = String::new();
+    let mut last = 0;
+
+    for range in ranges {
+        highlighted.push_str(&line[last..range.start]);
+        highlighted.push_str(&line[range.clone()].red().bold().to_string());
+        last = range.end;
+    }
+    highlighted.push_str(&line[last..]);
+
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::program::Program;
+
+    fn line_match(context: RangeInclusive<usize>) -> LineMatch {
+        LineMatch {
+            line_number: *context.start(),
+            line: String::new(),
+            ranges: Vec::new(),
+            score: 0.0,
+            context,
+            section: None,
+        }
+    }
+
+    #[test]
+    fn merges_overlapping_and_touching_snippets() {
+        let matches = [line_match(1..=3), line_match(3..=5), line_match(6..=6)];
+        assert_eq!(merge_snippets(&matches), [1..=6]);
+    }
+
+    #[test]
+    fn keeps_separate_snippets_in_match_order() {
+        let matches = [line_match(20..=22), line_match(1..=2), line_match(10..=12)];
+        assert_eq!(merge_snippets(&matches), [20..=22, 1..=2, 10..=12]);
+    }
+
+    #[test]
+    fn merges_snippets_joined_by_a_later_one() {
+        let matches = [line_match(10..=12), line_match(1..=3), line_match(4..=9)];
+        assert_eq!(merge_snippets(&matches), [1..=12]);
+    }
+
+    #[test]
+    fn writes_hits_in_the_versioned_json_schema() {
+        let mut program = Program::new("ls", Vec::new(), "Usage: ls\n  -a, --all\n    show all\n");
+        program.id = Some(7);
+        let mut result = SearchResult::new(program, 2.5);
+        result.lines.push(LineMatch {
+            line_number: 2,
+            line: "  -a, --all".to_string(),
+            ranges: vec![2..4, 6..11],
+            score: 1.0,
+            context: 2..=3,
+            section: Some("Options".to_string()),
+        });
+
+        let hits = serde_json::to_value(json_hits(&[result])).unwrap();
+        assert_eq!(
+            hits,
+            serde_json::json!([{
+                "schema_version": 1,
+                "program": "ls",
+                "program_id": 7,
+                "line_number": 2,
+                "line": "  -a, --all",
+                "ranges": [{ "start": 2, "end": 4 }, { "start": 6, "end": 11 }],
+                "section": "Options",
+                "snippet": {
+                    "start_line": 2,
+                    "end_line": 3,
+                    "text": "  -a, --all\n    show all",
+                },
+                "score": 1.0,
+                "program_score": 2.5,
+            }])
+        );
+        assert_eq!(JSON_SCHEMA_VERSION, 1);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::db::{alias::Alias, program::Program};
+use crate::search::errors::SearchError;
+use glob::{MatchOptions, Pattern};
+use rusqlite::Connection;
+use strsim::normalized_levenshtein;
+
+// Names less similar than this are not suggested
+const SUGGESTION_THRESHOLD: f64 = 0.4;
+
+// Resolve program names, aliases and glob patterns to the ids of the programs they refer to
+pub fn resolve_programs(conn: &Connection, names: &[String]) -> Result<Vec<i32>, SearchError> {
+    let mut ids = Vec::new();
+
+    for name in names {
+        let matched = if name.contains(['*', '?', '[']) {
+            let pattern = Pattern::new(name)?;
+            let options = MatchOptions {
+                case_sensitive: false,
+                ..MatchOptions::new()
+            };
+            matching_ids(conn, |n| pattern.matches_with(n, options))?
+        } else {
+            match Program::get_by_name_or_alias(conn, name)? {
+                Some(program) => program.id.into_iter().collect::<Vec<i32>>(),
+                // Like globs, names match regardless of case when none matches exactly
+                None => {
+                    let name = name.to_lowercase();
+                    matching_ids(conn, |n| n.to_lowercase() == name)?
+                }
+            }
+        };
+
+        if matched.is_empty() {
+            return Err(SearchError::UnknownProgram(
+                name.to_string(),
+                closest_names(conn, name)?,
+            ));
+        }
+
+        // A program includes its subcommands
+        for id in matched {
+            let descendants = Program::get_descendant_ids(conn, id)?;
+            for id in std::iter::once(id).chain(descendants) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+
+    Ok(ids)
+}
+
+// Get the ids of the programs whose name or one of whose aliases matches
+fn matching_ids(
+    conn: &Connection,
+    matches: impl Fn(&str) -> bool,
+) -> Result<Vec<i32>, SearchError> {
+    let mut ids = Program::get_all(conn)?
+        .into_iter()
+        .filter(|p| matches(&p.name))
+        .filter_map(|p| p.id)
+        .collect::<Vec<i32>>();
+    ids.extend(
+        Alias::get_all(conn)?
+            .into_iter()
+            .filter(|a| matches(&a.alias))
+            .map(|a| a.program_id),
+    );
+    Ok(ids)
+}
+
+// Find the stored program names and aliases that look most like the given name
+fn closest_names(conn: &Connection, name: &str) -> Result<Vec<String>, SearchError> {
+    let name = name.to_lowercase();
+
+    let mut scored = Program::get_all(conn)?
+        .into_iter()
+        .map(|p| p.name)
+        .chain(Alias::get_all(conn)?.into_iter().map(|a| a.alias))
+        .map(|n| (normalized_levenshtein(&name, &n.to_lowercase()), n))
+        .filter(|(score, _)| *score >= SUGGESTION_THRESHOLD)
+        .collect::<Vec<(f64, String)>>();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    Ok(scored.into_iter().take(3).map(|(_, name)| name).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::help_command::HelpCommand;
+    use crate::db::open_test_connection;
+
+    fn database() -> Connection {
+        let conn = open_test_connection();
+        for name in ["Nmap", "docker", "docker-compose", "ls"] {
+            let command = format!("{} --help", name.to_lowercase());
+            Program::new(name, vec![HelpCommand::parse(&command, false).unwrap()], "")
+                .insert(&conn)
+                .unwrap();
+        }
+        let mut run = Program::new("docker run", Vec::new(), "");
+        run.parent_id = Some(2);
+        run.insert(&conn).unwrap();
+        Alias {
+            alias: "list".to_string(),
+            program_id: 4,
+        }
+        .insert(&conn)
+        .unwrap();
+        conn
+    }
+
+    fn resolve(conn: &Connection, names: &[&str]) -> Result<Vec<i32>, SearchError> {
+        let names = names.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+        resolve_programs(conn, &names)
+    }
+
+    #[test]
+    fn resolves_names_aliases_and_globs_regardless_of_case() {
+        let conn = database();
+
+        assert_eq!(resolve(&conn, &["nmap"]).unwrap(), [1]);
+        assert_eq!(resolve(&conn, &["NMAP*"]).unwrap(), [1]);
+        assert_eq!(resolve(&conn, &["LIST"]).unwrap(), [4]);
+        assert_eq!(resolve(&conn, &["docker*", "ls"]).unwrap(), [2, 5, 3, 4]);
+    }
+
+    #[test]
+    fn suggests_close_names_for_unknown_programs() {
+        let conn = database();
+
+        match resolve(&conn, &["dokcer"]) {
+            Err(SearchError::UnknownProgram(name, suggestions)) => {
+                assert_eq!(name, "dokcer");
+                assert_eq!(suggestions[0], "docker");
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert_eq!(closest_names(&conn, "LST").unwrap(), ["list", "ls"]);
+        assert!(closest_names(&conn, "zzzzzz").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,14 @@
+use crate::db::section::SectionKind;
+use crate::search::context::ContextMode;
+
+#[derive(Debug, Default)]
+pub struct SearchOptions {
+    pub fuzzy: bool,
+    pub options: bool,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub word: bool,
+    pub program_ids: Option<Vec<i32>>,
+    pub sections: Option<Vec<SectionKind>>,
+    pub context: ContextMode,
+}
@@ -0,0 +1,37 @@
+use crate::db::{program::Program, program_option::ProgramOption};
+use std::ops::{Range, RangeInclusive};
+
+#[derive(Debug)]
+pub struct LineMatch {
+    pub line_number: usize,
+    pub line: String,
+    pub ranges: Vec<Range<usize>>,
+    pub score: f64,
+    // Line numbers of the snippet shown around the match
+    pub context: RangeInclusive<usize>,
+    // Heading of the section the line is in
+    pub section: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct SearchResult {
+    pub program: Program,
+    pub score: f64,
+    pub lines: Vec<LineMatch>,
+}
+
+impl SearchResult {
+    pub fn new(program: Program, score: f64) -> Self {
+        SearchResult {
+            program,
+            score,
+            lines: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OptionResult {
+    pub program: Program,
+    pub options: Vec<ProgramOption>,
+}